@@ -3,6 +3,7 @@
 #![no_std]
 #![no_main]
 
+#[cfg(not(feature = "panic-serial"))]
 use panic_halt as _;
 use rp2040_hal as hal;
 use hal::pac;
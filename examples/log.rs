@@ -0,0 +1,108 @@
+//! Route `log` records to the USB serial port.
+
+#![no_std]
+#![no_main]
+
+#[cfg(not(feature = "panic-serial"))]
+use panic_halt as _;
+use rp2040_hal as hal;
+use hal::pac;
+use rp2040_hal::clocks::Clock;
+
+use log::LevelFilter;
+use usb_device::{class_prelude::*, prelude::*};
+use usbd_serial::SerialPort;
+
+use serial_write::logger::SerialLogger;
+
+#[link_section = ".boot2"]
+#[used]
+pub static BOOT2: [u8; 256] = rp2040_boot2::BOOT_LOADER_GENERIC_03H;
+
+const XTAL_FREQ_HZ: u32 = 12_000_000u32;
+
+static LOGGER: SerialLogger<1024> = SerialLogger::new()
+    .with_max_level(LevelFilter::Debug)
+    .with_targets(&[("usb_device", LevelFilter::Warn)])
+    .with_clock(uptime_us);
+
+/// Microseconds since boot, read from the free-running timer.
+fn uptime_us() -> u64 {
+    let timer = unsafe { &*pac::TIMER::ptr() };
+    loop {
+        let hi = timer.timerawh.read().bits();
+        let lo = timer.timerawl.read().bits();
+        if timer.timerawh.read().bits() == hi {
+            return ((hi as u64) << 32) | lo as u64;
+        }
+    }
+}
+
+#[rp2040_hal::entry]
+fn main() -> ! {
+    let mut pac = pac::Peripherals::take().unwrap();
+    let core = pac::CorePeripherals::take().unwrap();
+
+    let mut watchdog = hal::Watchdog::new(pac.WATCHDOG);
+
+    let clocks = hal::clocks::init_clocks_and_plls(
+        XTAL_FREQ_HZ,
+        pac.XOSC,
+        pac.CLOCKS,
+        pac.PLL_SYS,
+        pac.PLL_USB,
+        &mut pac.RESETS,
+        &mut watchdog,
+    )
+    .ok()
+    .unwrap();
+
+    let mut delay = cortex_m::delay::Delay::new(core.SYST, clocks.system_clock.freq().to_Hz());
+
+    // Start the timer used by `uptime_us`.
+    let _timer = hal::Timer::new(pac.TIMER, &mut pac.RESETS);
+
+    // Set the USB bus
+    let usb_bus = UsbBusAllocator::new(hal::usb::UsbBus::new(
+        pac.USBCTRL_REGS,
+        pac.USBCTRL_DPRAM,
+        clocks.usb_clock,
+        true,
+        &mut pac.RESETS,
+    ));
+
+    // Set the serial port
+    let mut serial = SerialPort::new(&usb_bus);
+
+    // Set a USB device
+    let mut usb_dev = UsbDeviceBuilder::new(&usb_bus, UsbVidPid(0x16c0, 0x27dd))
+        .manufacturer("Fake company")
+        .product("Serial port")
+        .serial_number("TEST")
+        .device_class(2)
+        .build();
+
+    // Register the logger.
+    LOGGER.init();
+
+    let mut count = 0u32;
+    loop {
+        for _ in 0..200 {
+            delay.delay_ms(5);
+            let _ = usb_dev.poll(&mut [&mut serial]);
+            // Send the buffered records.
+            let _ = LOGGER.drain(&mut serial);
+        }
+
+        // "[    1.000123] [INFO log] tick 0"
+        log::info!("tick {}", count);
+        // `u32::is_multiple_of` needs Rust 1.87.
+        #[allow(clippy::manual_is_multiple_of)]
+        if count % 10 == 0 {
+            log::warn!("{} ticks elapsed", count);
+        }
+        count += 1;
+    }
+}
+
+// End of file
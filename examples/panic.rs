@@ -0,0 +1,88 @@
+//! Report a panic over the USB serial port.
+
+#![no_std]
+#![no_main]
+
+use rp2040_hal as hal;
+use hal::pac;
+use rp2040_hal::clocks::Clock;
+
+use usb_device::{class_prelude::*, prelude::*};
+use usbd_serial::SerialPort;
+
+use serial_write::panic::{self, PanicAction};
+use serial_write::{UsbSerial, Writer};
+
+#[link_section = ".boot2"]
+#[used]
+pub static BOOT2: [u8; 256] = rp2040_boot2::BOOT_LOADER_GENERIC_03H;
+
+const XTAL_FREQ_HZ: u32 = 12_000_000u32;
+
+#[rp2040_hal::entry]
+fn main() -> ! {
+    let mut pac = pac::Peripherals::take().unwrap();
+    let core = pac::CorePeripherals::take().unwrap();
+
+    let mut watchdog = hal::Watchdog::new(pac.WATCHDOG);
+
+    let clocks = hal::clocks::init_clocks_and_plls(
+        XTAL_FREQ_HZ,
+        pac.XOSC,
+        pac.CLOCKS,
+        pac.PLL_SYS,
+        pac.PLL_USB,
+        &mut pac.RESETS,
+        &mut watchdog,
+    )
+    .ok()
+    .unwrap();
+
+    let mut delay = cortex_m::delay::Delay::new(core.SYST, clocks.system_clock.freq().to_Hz());
+
+    // Set the USB bus, which must live as long as the panic handler may use it.
+    let usb_bus = cortex_m::singleton!(: UsbBusAllocator<hal::usb::UsbBus> = UsbBusAllocator::new(
+        hal::usb::UsbBus::new(
+            pac.USBCTRL_REGS,
+            pac.USBCTRL_DPRAM,
+            clocks.usb_clock,
+            true,
+            &mut pac.RESETS,
+        )
+    ))
+    .unwrap();
+
+    // Set the serial port
+    let serial = SerialPort::new(usb_bus);
+
+    // Set a USB device
+    let usb_dev = UsbDeviceBuilder::new(usb_bus, UsbVidPid(0x16c0, 0x27dd))
+        .manufacturer("Fake company")
+        .product("Serial port")
+        .serial_number("TEST")
+        .device_class(2)
+        .build();
+
+    // Register the serial port for the panic handler.
+    let usb = cortex_m::singleton!(: UsbSerial<'static, hal::usb::UsbBus> = UsbSerial::new(usb_dev, serial)).unwrap();
+    panic::register(usb, PanicAction::Reset { polls: 1_000_000 });
+
+    let mut writer = Writer::new();
+
+    for count in (0..10u8).rev() {
+        for _ in 0..200 {
+            delay.delay_ms(5);
+            panic::with_sink(|usb| usb.poll());
+        }
+        panic::with_sink(|usb| {
+            let _ = writer.write_str("Panic in ", usb);
+            let _ = writer.writeln_u8(count, usb);
+        });
+    }
+
+    // "panicked at examples/panic.rs:85:5:"
+    // "Goodbye!"
+    panic!("Goodbye!");
+}
+
+// End of file
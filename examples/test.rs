@@ -3,6 +3,7 @@
 #![no_std]
 #![no_main]
 
+#[cfg(not(feature = "panic-serial"))]
 use panic_halt as _;
 use rp2040_hal as hal;
 use hal::pac;
@@ -141,11 +142,11 @@ fn main() -> ! {
         
         let _ = writer.writeln_str("===============================================", &mut serial);
         write_check_float!(
-            "340282350000000000000000000000000000000.0 [f32]: ",
+            "340282346638528859811704183484516925440.0 [f32]: ",
             f32::MAX, 1, write_f32, writer, serial
         );
         write_check_float!(
-            "-340282350000000000000000000000000000000.0 [f32]: ",
+            "-340282346638528859811704183484516925440.0 [f32]: ",
             f32::MIN, 1, write_f32, writer, serial
         );
         for (i, &s) in [
@@ -172,8 +173,8 @@ fn main() -> ! {
             f32::MIN, 7, write_f32_exp, writer, serial
         );
         for (i, &s) in [
-            " 1.1e-07 [f32]: ", " 1.19e-07 [f32]: ", " 1.192e-07 [f32]: ", " 1.1920e-07 [f32]: ", 
-            " 1.19209e-07 [f32]: ", " 1.192092e-07 [f32]: ", " 1.1920929e-07 [f32]: ",
+            " 1.2e-07 [f32]: ", " 1.19e-07 [f32]: ", " 1.192e-07 [f32]: ", " 1.1921e-07 [f32]: ", 
+            " 1.19209e-07 [f32]: ", " 1.192093e-07 [f32]: ", " 1.1920929e-07 [f32]: ",
             ].iter().enumerate() 
         {
             write_check_float!(s, f32::EPSILON, i+1, write_f32_exp, writer, serial);
@@ -195,8 +196,8 @@ fn main() -> ! {
             f64::MIN, 16, write_f64_exp, writer, serial
         );
         for (i, &s) in [
-            " 2.2e-16 [f64]: ", " 2.22e-16 [f64]: ", " 2.220e-16 [f64]: ", " 2.2204e-16 [f64]: ", " 2.22044e-16 [f64]: ", " 2.220446e-16 [f64]: ", 
-            " 2.2204460e-16 [f64]: ", " 2.22044604e-16 [f64]: ", " 2.220446049e-16 [f64]: ", " 2.2204460492e-16 [f64]: ", " 2.22044604925e-16 [f64]: ", 
+            " 2.2e-16 [f64]: ", " 2.22e-16 [f64]: ", " 2.220e-16 [f64]: ", " 2.2204e-16 [f64]: ", " 2.22045e-16 [f64]: ", " 2.220446e-16 [f64]: ", 
+            " 2.2204460e-16 [f64]: ", " 2.22044605e-16 [f64]: ", " 2.220446049e-16 [f64]: ", " 2.2204460493e-16 [f64]: ", " 2.22044604925e-16 [f64]: ", 
             " 2.220446049250e-16 [f64]: ", " 2.2204460492503e-16 [f64]: ", " 2.22044604925031e-16 [f64]: ", " 2.220446049250313e-16 [f64]: "
 
             ].iter().enumerate() 
@@ -220,7 +221,7 @@ fn main() -> ! {
             &[1.0, 23.45, 678.901], 2, write_f32_slice, writer, serial
         );
         write_check_float!(
-            "[  1.00e000,  2.34e001,  6.78e002, ] [ [f32] ]: ",
+            "[  1.00e000,  2.35e001,  6.79e002, ] [ [f32] ]: ",
             &[1.0, 23.45, 678.901], 2, write_f32_slice_exp, writer, serial
         );
         let _ = writer.writeln_str("===============================================", &mut serial);
@@ -0,0 +1,77 @@
+//! Number parsing, line input and frame decoding, with arbitrary bytes.
+//!
+//! Checks that nothing panics, and that written numbers are read back as the same values.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use serial_write::frame::{Checksum, FrameDecoder};
+use serial_write::input::LineReader;
+use serial_write::{parse, SliceSink, Source, Writer};
+use usb_device::UsbError;
+
+struct Bytes<'a>(&'a [u8]);
+
+impl Source for Bytes<'_> {
+    fn read(&mut self, data: &mut [u8]) -> Result<usize, UsbError> {
+        let n = data.len().min(self.0.len());
+        if n == 0 {
+            return Err(UsbError::WouldBlock);
+        }
+        data[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse::read_i64(data);
+    let _ = parse::read_u64_hex(data);
+    let _ = parse::read_u32_bin(data);
+    let _ = parse::read_f32(data);
+    let _ = parse::read_f64(data);
+    let _ = parse::read_i16_slice(data, &mut [0; 8]);
+    let _ = parse::read_f64_slice(data, &mut [0.0; 8]);
+
+    let mut reader = LineReader::<32>::new();
+    let mut source = Bytes(data);
+    while !source.0.is_empty() {
+        let _ = reader.read_line(&mut source);
+    }
+
+    let mut decoder = FrameDecoder::<64>::new(Checksum::Crc16);
+    for &byte in data {
+        if let Some(Ok(frame)) = decoder.feed(byte) {
+            let _ = frame.reader().remaining();
+        }
+    }
+
+    // Written numbers are read back as the same values.
+    if data.len() >= 8 {
+        let bits = u64::from_le_bytes(data[..8].try_into().unwrap());
+        let mut buf = [0u8; 400];
+        let mut writer = Writer::new();
+
+        let mut sink = SliceSink::new(&mut buf);
+        writer.write_i64(bits as i64, &mut sink).unwrap();
+        assert_eq!(parse::read_i64(sink.as_bytes()), Ok(bits as i64));
+
+        let mut sink = SliceSink::new(&mut buf);
+        writer.write_u64_hex(bits, &mut sink).unwrap();
+        assert_eq!(parse::read_u64_hex(sink.as_bytes()), Ok(bits));
+
+        // 17 significant digits are enough for any `f64`, and 9 for any `f32`.
+        let val = f64::from_bits(bits);
+        if val.is_finite() {
+            let mut sink = SliceSink::new(&mut buf);
+            writer.write_f64_exp(val, 16, &mut sink).unwrap();
+            assert_eq!(parse::read_f64(sink.as_bytes()), Ok(val));
+        }
+        let val = f32::from_bits(bits as u32);
+        if val.is_finite() {
+            let mut sink = SliceSink::new(&mut buf);
+            writer.write_f32_exp(val, 8, &mut sink).unwrap();
+            assert_eq!(parse::read_f32(sink.as_bytes()), Ok(val));
+        }
+    }
+});
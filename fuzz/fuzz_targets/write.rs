@@ -0,0 +1,139 @@
+//! Every number writer of `Writer`, with arbitrary values and decimal places.
+//!
+//! Checks that the output is within the maximum length of the README, that the returned count is
+//! the number of bytes written, and that no call loops forever.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use serial_write::{Sink, Writer};
+use usb_device::UsbError;
+
+/// Number of writes of one call after which it is taken to loop forever.
+const STEP_BUDGET: usize = 100_000;
+
+/// Buffer sink counting the writes.
+struct Budget {
+    data: Vec<u8>,
+    steps: usize,
+}
+
+impl Sink for Budget {
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        self.steps += 1;
+        assert!(self.steps <= STEP_BUDGET, "more than {} writes", STEP_BUDGET);
+        self.data.extend_from_slice(data);
+        Ok(data.len())
+    }
+}
+
+/// Floats are given as bits, to cover NaN payloads, subnormals and ±0.
+#[derive(Arbitrary, Debug)]
+enum Call {
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    Isize(isize),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    Usize(usize),
+    U8Hex(u8),
+    U16Hex(u16),
+    U32Hex(u32),
+    U64Hex(u64),
+    UsizeHex(usize),
+    F32(u32, u8),
+    F64(u64, u8),
+    F32Exp(u32, u8),
+    F64Exp(u64, u8),
+    I8Slice(Vec<i8>),
+    I16Slice(Vec<i16>),
+    I32Slice(Vec<i32>),
+    I64Slice(Vec<i64>),
+    IsizeSlice(Vec<isize>),
+    U8Slice(Vec<u8>),
+    U16Slice(Vec<u16>),
+    U32Slice(Vec<u32>),
+    U64Slice(Vec<u64>),
+    UsizeSlice(Vec<usize>),
+    F32Slice(Vec<u32>, u8),
+    F64Slice(Vec<u64>, u8),
+    F32SliceExp(Vec<u32>, u8),
+    F64SliceExp(Vec<u64>, u8),
+}
+
+/// Maximum length of an integer of `bytes` bytes in decimal.
+fn int_len(bytes: usize, signed: bool) -> usize {
+    match (bytes, signed) {
+        (1, true) => 4,
+        (2, true) => 6,
+        (4, true) => 11,
+        (8, true) => 20,
+        (1, false) => 3,
+        (2, false) => 5,
+        (4, false) => 10,
+        _ => 20,
+    }
+}
+
+fn slice_len(len: usize, element: usize) -> usize {
+    3 + len * (2 + element)
+}
+
+fn f32s(bits: &[u32]) -> Vec<f32> {
+    bits.iter().map(|&b| f32::from_bits(b)).collect()
+}
+
+fn f64s(bits: &[u64]) -> Vec<f64> {
+    bits.iter().map(|&b| f64::from_bits(b)).collect()
+}
+
+fuzz_target!(|call: Call| {
+    let mut writer = Writer::new();
+    let mut sink = Budget { data: Vec::new(), steps: 0 };
+    let s = &mut sink;
+    let size = core::mem::size_of::<usize>();
+    let (result, max) = match call {
+        Call::I8(v) => (writer.write_i8(v, s), int_len(1, true)),
+        Call::I16(v) => (writer.write_i16(v, s), int_len(2, true)),
+        Call::I32(v) => (writer.write_i32(v, s), int_len(4, true)),
+        Call::I64(v) => (writer.write_i64(v, s), int_len(8, true)),
+        Call::Isize(v) => (writer.write_isize(v, s), int_len(size, true)),
+        Call::U8(v) => (writer.write_u8(v, s), int_len(1, false)),
+        Call::U16(v) => (writer.write_u16(v, s), int_len(2, false)),
+        Call::U32(v) => (writer.write_u32(v, s), int_len(4, false)),
+        Call::U64(v) => (writer.write_u64(v, s), int_len(8, false)),
+        Call::Usize(v) => (writer.write_usize(v, s), int_len(size, false)),
+        Call::U8Hex(v) => (writer.write_u8_hex(v, s), 2 + 2),
+        Call::U16Hex(v) => (writer.write_u16_hex(v, s), 2 + 4),
+        Call::U32Hex(v) => (writer.write_u32_hex(v, s), 2 + 8),
+        Call::U64Hex(v) => (writer.write_u64_hex(v, s), 2 + 16),
+        Call::UsizeHex(v) => (writer.write_usize_hex(v, s), 2 + 2 * size),
+        Call::F32(b, nodp) => (writer.write_f32(f32::from_bits(b), nodp as usize, s), 41 + nodp as usize),
+        Call::F64(b, nodp) => (writer.write_f64(f64::from_bits(b), nodp as usize, s), 311 + nodp as usize),
+        Call::F32Exp(b, nodp) => (writer.write_f32_exp(f32::from_bits(b), nodp as usize, s), 7 + nodp as usize),
+        Call::F64Exp(b, nodp) => (writer.write_f64_exp(f64::from_bits(b), nodp as usize, s), 8 + nodp as usize),
+        Call::I8Slice(v) => (writer.write_i8_slice(&v, s), slice_len(v.len(), int_len(1, true))),
+        Call::I16Slice(v) => (writer.write_i16_slice(&v, s), slice_len(v.len(), int_len(2, true))),
+        Call::I32Slice(v) => (writer.write_i32_slice(&v, s), slice_len(v.len(), int_len(4, true))),
+        Call::I64Slice(v) => (writer.write_i64_slice(&v, s), slice_len(v.len(), int_len(8, true))),
+        Call::IsizeSlice(v) => (writer.write_isize_slice(&v, s), slice_len(v.len(), int_len(size, true))),
+        Call::U8Slice(v) => (writer.write_u8_slice(&v, s), slice_len(v.len(), int_len(1, false))),
+        Call::U16Slice(v) => (writer.write_u16_slice(&v, s), slice_len(v.len(), int_len(2, false))),
+        Call::U32Slice(v) => (writer.write_u32_slice(&v, s), slice_len(v.len(), int_len(4, false))),
+        Call::U64Slice(v) => (writer.write_u64_slice(&v, s), slice_len(v.len(), int_len(8, false))),
+        Call::UsizeSlice(v) => (writer.write_usize_slice(&v, s), slice_len(v.len(), int_len(size, false))),
+        Call::F32Slice(v, nodp) => (writer.write_f32_slice(&f32s(&v), nodp as usize, s), slice_len(v.len(), 41 + nodp as usize)),
+        Call::F64Slice(v, nodp) => (writer.write_f64_slice(&f64s(&v), nodp as usize, s), slice_len(v.len(), 311 + nodp as usize)),
+        Call::F32SliceExp(v, nodp) => (writer.write_f32_slice_exp(&f32s(&v), nodp as usize, s), slice_len(v.len(), 7 + nodp as usize)),
+        Call::F64SliceExp(v, nodp) => (writer.write_f64_slice_exp(&f64s(&v), nodp as usize, s), slice_len(v.len(), 8 + nodp as usize)),
+    };
+    let count = result.unwrap();
+    assert_eq!(count, sink.data.len());
+    assert!(count <= max, "{} bytes, more than {}", count, max);
+    assert!(core::str::from_utf8(&sink.data).is_ok());
+});
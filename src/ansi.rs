@@ -0,0 +1,182 @@
+//! ANSI escape sequences for colours, text attributes and cursor control.
+//!
+//! The sequences are output without the line prefix of `Writer`.
+//! Without the `ansi` feature (enabled by default), these methods output nothing,
+//! so that the same code prints plain text to a terminal or tool which does not understand them.
+//!
+//! ```ignore
+//! writer.write_fg(Color::Red, &mut serial)?;
+//! writer.write_bold(&mut serial)?;
+//! writer.write_str("ERROR", &mut serial)?;
+//! writer.write_reset(&mut serial)?;
+//!
+//! // Status line updated in place
+//! writer.write_save_cursor(&mut serial)?;
+//! writer.write_cursor_to(1, 1, &mut serial)?;
+//! writer.write_clear_line(&mut serial)?;
+//! writer.write_str("battery: 87%", &mut serial)?;
+//! writer.write_restore_cursor(&mut serial)?;
+//! ```
+
+use numtoa::NumToA;
+use usb_device::class_prelude::UsbError;
+
+use crate::{Sink, Writer};
+
+/// Colour of text or background.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    /// Colour of the 256-colour palette.
+    Indexed(u8),
+    /// 24-bit colour.
+    Rgb(u8, u8, u8),
+}
+
+impl Writer {
+    /// Output `ESC [ params final`, e.g. `ESC [ 1 ; 31 m`.
+    fn _csi<S: Sink + ?Sized>(&mut self, params: &[u8], final_byte: u8, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        if !cfg!(feature = "ansi") {
+            return Ok(0);
+        }
+        let mut count = 0;
+        handle_err1!(serial.write(b"\x1b["), count);
+        for (i, &param) in params.iter().enumerate() {
+            if i > 0 {
+                handle_err1!(serial.write(b";"), count);
+            }
+            handle_err1!(serial.write(param.numtoa(10, &mut self.buf)), count);
+        }
+        handle_err1!(serial.write(&[final_byte]), count);
+        Ok(count)
+    }
+    /// Output SGR of `color`, with `base` 30 for text and 40 for background.
+    fn _color<S: Sink + ?Sized>(&mut self, color: Color, base: u8, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let (offset, bright) = match color {
+            Color::Black => (0, false),
+            Color::Red => (1, false),
+            Color::Green => (2, false),
+            Color::Yellow => (3, false),
+            Color::Blue => (4, false),
+            Color::Magenta => (5, false),
+            Color::Cyan => (6, false),
+            Color::White => (7, false),
+            Color::BrightBlack => (0, true),
+            Color::BrightRed => (1, true),
+            Color::BrightGreen => (2, true),
+            Color::BrightYellow => (3, true),
+            Color::BrightBlue => (4, true),
+            Color::BrightMagenta => (5, true),
+            Color::BrightCyan => (6, true),
+            Color::BrightWhite => (7, true),
+            Color::Indexed(n) => { return self._csi(&[base + 8, 5, n], b'm', serial); }
+            Color::Rgb(r, g, b) => { return self._csi(&[base + 8, 2, r, g, b], b'm', serial); }
+        };
+        let code = if bright { base + 60 + offset } else { base + offset };
+        self._csi(&[code], b'm', serial)
+    }
+    /// Set the colour of the text.
+    pub fn write_fg<S: Sink + ?Sized>(&mut self, color: Color, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        self._color(color, 30, serial)
+    }
+    /// Set the colour of the background.
+    pub fn write_bg<S: Sink + ?Sized>(&mut self, color: Color, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        self._color(color, 40, serial)
+    }
+    /// Make the text bold.
+    pub fn write_bold<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        self._csi(&[1], b'm', serial)
+    }
+    /// Underline the text.
+    pub fn write_underline<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        self._csi(&[4], b'm', serial)
+    }
+    /// Reset the colours and attributes.
+    pub fn write_reset<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        self._csi(&[0], b'm', serial)
+    }
+    /// Clear the screen and move the cursor to the top left.
+    pub fn write_clear_screen<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        handle_err2!(self._csi(&[2], b'J', serial), count);
+        handle_err2!(self._csi(&[], b'H', serial), count);
+        Ok(count)
+    }
+    /// Clear the line of the cursor and move the cursor to its start.
+    pub fn write_clear_line<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        handle_err2!(self._csi(&[2], b'K', serial), count);
+        if cfg!(feature = "ansi") {
+            handle_err1!(serial.write(b"\r"), count);
+        }
+        Ok(count)
+    }
+    /// Clear from the cursor to the end of the line.
+    pub fn write_clear_to_eol<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        self._csi(&[], b'K', serial)
+    }
+    /// Move the cursor to `row` and `col`, both starting from 1.
+    pub fn write_cursor_to<S: Sink + ?Sized>(&mut self, row: u16, col: u16, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        if !cfg!(feature = "ansi") {
+            return Ok(0);
+        }
+        let mut count = 0;
+        handle_err1!(serial.write(b"\x1b["), count);
+        handle_err1!(serial.write(row.numtoa(10, &mut self.buf)), count);
+        handle_err1!(serial.write(b";"), count);
+        handle_err1!(serial.write(col.numtoa(10, &mut self.buf)), count);
+        handle_err1!(serial.write(b"H"), count);
+        Ok(count)
+    }
+    /// Save the position and attributes of the cursor.
+    pub fn write_save_cursor<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        if !cfg!(feature = "ansi") {
+            return Ok(0);
+        }
+        let mut count = 0;
+        handle_err1!(serial.write(b"\x1b7"), count);
+        Ok(count)
+    }
+    /// Restore the position and attributes saved by `write_save_cursor`.
+    pub fn write_restore_cursor<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        if !cfg!(feature = "ansi") {
+            return Ok(0);
+        }
+        let mut count = 0;
+        handle_err1!(serial.write(b"\x1b8"), count);
+        Ok(count)
+    }
+    /// Hide the cursor.
+    pub fn write_hide_cursor<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        if !cfg!(feature = "ansi") {
+            return Ok(0);
+        }
+        let mut count = 0;
+        handle_err1!(serial.write(b"\x1b[?25l"), count);
+        Ok(count)
+    }
+    /// Show the cursor hidden by `write_hide_cursor`.
+    pub fn write_show_cursor<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        if !cfg!(feature = "ansi") {
+            return Ok(0);
+        }
+        let mut count = 0;
+        handle_err1!(serial.write(b"\x1b[?25h"), count);
+        Ok(count)
+    }
+}
@@ -0,0 +1,222 @@
+//! Allocation-free CBOR (RFC 8949) encoder.
+//!
+//! Values are written to the sink as they are encoded. Nothing checks that the items of arrays and maps
+//! match the announced lengths; an indefinite-length array, map or string is closed by `write_break`.
+//!
+//! ```ignore
+//! let mut cbor = CborWriter::new();
+//! cbor.begin_map(2, &mut serial)?;
+//! cbor.write_str("t", &mut serial)?;
+//! cbor.write_u32(1200, &mut serial)?;
+//! cbor.write_str("acc", &mut serial)?;
+//! cbor.write_f32_slice(&[0.1, -0.2, 9.8], &mut serial)?;
+//! // In Python: cbor2.loads(data) == {"t": 1200, "acc": [0.1, -0.2, 9.8]} (as f32)
+//! ```
+
+use usb_device::class_prelude::UsbError;
+
+use crate::sink::write_all;
+use crate::Sink;
+
+const UNSIGNED: u8 = 0;
+const NEGATIVE: u8 = 1;
+const BYTES: u8 = 2;
+const TEXT: u8 = 3;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+const TAG: u8 = 6;
+const SIMPLE: u8 = 7;
+
+/// Additional information of indefinite lengths.
+const INDEFINITE: u8 = 31;
+
+macro_rules! write_uint {
+    ($(#[$meta: meta])*
+    $type: ty, $name: ident) => {
+        $(#[$meta])*
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: $type, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+            write_head(UNSIGNED, val as u64, serial)
+        }
+    };
+}
+macro_rules! write_sint {
+    ($(#[$meta: meta])*
+    $type: ty, $name: ident) => {
+        $(#[$meta])*
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: $type, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+            self.write_i64(val as i64, serial)
+        }
+    };
+}
+macro_rules! write_slice {
+    ($(#[$meta: meta])*
+    $type: ty, $name: ident, $f: ident) => {
+        $(#[$meta])*
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: &[$type], serial: &mut S) -> Result<usize, (UsbError, usize)> {
+            let mut count = 0;
+            handle_err2!(self.begin_array(val.len(), serial), count);
+            for &v in val.iter() {
+                handle_err2!(self.$f(v, serial), count);
+            }
+            Ok(count)
+        }
+    };
+}
+
+/// Writer of CBOR data items.
+pub struct CborWriter;
+
+impl CborWriter {
+    /// Initialize CborWriter.
+    pub const fn new() -> Self {
+        Self
+    }
+    write_uint!(/// Output a `u8` as an unsigned integer.
+        u8, write_u8);
+    write_uint!(/// Output a `u16` as an unsigned integer.
+        u16, write_u16);
+    write_uint!(/// Output a `u32` as an unsigned integer.
+        u32, write_u32);
+    write_uint!(/// Output a `u64` as an unsigned integer.
+        u64, write_u64);
+    write_uint!(/// Output a `usize` as an unsigned integer.
+        usize, write_usize);
+    write_sint!(/// Output an `i8` as an unsigned or negative integer.
+        i8, write_i8);
+    write_sint!(/// Output an `i16` as an unsigned or negative integer.
+        i16, write_i16);
+    write_sint!(/// Output an `i32` as an unsigned or negative integer.
+        i32, write_i32);
+    write_sint!(/// Output an `isize` as an unsigned or negative integer.
+        isize, write_isize);
+    /// Output an `i64` as an unsigned or negative integer.
+    pub fn write_i64<S: Sink + ?Sized>(&mut self, val: i64, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        if val < 0 {
+            // -1 - val, which cannot overflow.
+            write_head(NEGATIVE, !(val as u64), serial)
+        } else {
+            write_head(UNSIGNED, val as u64, serial)
+        }
+    }
+    /// Output an `f32` as a single-precision float.
+    pub fn write_f32<S: Sink + ?Sized>(&mut self, val: f32, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        handle_err2!(write_all(serial, &[SIMPLE << 5 | 26]), count);
+        handle_err2!(write_all(serial, &val.to_be_bytes()), count);
+        Ok(count)
+    }
+    /// Output an `f64` as a double-precision float.
+    pub fn write_f64<S: Sink + ?Sized>(&mut self, val: f64, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        handle_err2!(write_all(serial, &[SIMPLE << 5 | 27]), count);
+        handle_err2!(write_all(serial, &val.to_be_bytes()), count);
+        Ok(count)
+    }
+    /// Output `true` or `false`.
+    pub fn write_bool<S: Sink + ?Sized>(&mut self, val: bool, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        write_all(serial, &[SIMPLE << 5 | if val { 21 } else { 20 }])
+    }
+    /// Output `null`.
+    pub fn write_null<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        write_all(serial, &[SIMPLE << 5 | 22])
+    }
+    /// Output `undefined`.
+    pub fn write_undefined<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        write_all(serial, &[SIMPLE << 5 | 23])
+    }
+    /// Output a text string, or a chunk of an indefinite-length text string.
+    pub fn write_str<S: Sink + ?Sized>(&mut self, val: &str, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        handle_err2!(write_head(TEXT, val.len() as u64, serial), count);
+        handle_err2!(write_all(serial, val.as_bytes()), count);
+        Ok(count)
+    }
+    /// Output a byte string, or a chunk of an indefinite-length byte string.
+    pub fn write_bytes<S: Sink + ?Sized>(&mut self, val: &[u8], serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        handle_err2!(write_head(BYTES, val.len() as u64, serial), count);
+        handle_err2!(write_all(serial, val), count);
+        Ok(count)
+    }
+    /// Begin an array of `len` items.
+    pub fn begin_array<S: Sink + ?Sized>(&mut self, len: usize, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        write_head(ARRAY, len as u64, serial)
+    }
+    /// Begin a map of `len` key-value pairs. Write each key followed by its value.
+    pub fn begin_map<S: Sink + ?Sized>(&mut self, len: usize, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        write_head(MAP, len as u64, serial)
+    }
+    /// Begin an indefinite-length array, closed by `write_break`.
+    pub fn begin_array_indefinite<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        write_all(serial, &[ARRAY << 5 | INDEFINITE])
+    }
+    /// Begin an indefinite-length map, closed by `write_break`.
+    pub fn begin_map_indefinite<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        write_all(serial, &[MAP << 5 | INDEFINITE])
+    }
+    /// Begin an indefinite-length text string. Write the chunks with `write_str`, then close it by `write_break`.
+    pub fn begin_str_indefinite<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        write_all(serial, &[TEXT << 5 | INDEFINITE])
+    }
+    /// Begin an indefinite-length byte string. Write the chunks with `write_bytes`, then close it by `write_break`.
+    pub fn begin_bytes_indefinite<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        write_all(serial, &[BYTES << 5 | INDEFINITE])
+    }
+    /// Close the innermost indefinite-length item.
+    pub fn write_break<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        write_all(serial, &[SIMPLE << 5 | INDEFINITE])
+    }
+    /// Output a tag for the next item, e.g. 1 for epoch-based date/time.
+    pub fn write_tag<S: Sink + ?Sized>(&mut self, tag: u64, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        write_head(TAG, tag, serial)
+    }
+    write_slice!(/// Output the elements of `&[i16]` as an array.
+        i16, write_i16_slice, write_i16);
+    write_slice!(/// Output the elements of `&[u16]` as an array.
+        u16, write_u16_slice, write_u16);
+    write_slice!(/// Output the elements of `&[i32]` as an array.
+        i32, write_i32_slice, write_i32);
+    write_slice!(/// Output the elements of `&[u32]` as an array.
+        u32, write_u32_slice, write_u32);
+    write_slice!(/// Output the elements of `&[i64]` as an array.
+        i64, write_i64_slice, write_i64);
+    write_slice!(/// Output the elements of `&[u64]` as an array.
+        u64, write_u64_slice, write_u64);
+    write_slice!(/// Output the elements of `&[f32]` as an array of single-precision floats.
+        f32, write_f32_slice, write_f32);
+    write_slice!(/// Output the elements of `&[f64]` as an array of double-precision floats.
+        f64, write_f64_slice, write_f64);
+}
+
+impl Default for CborWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Output the initial byte of major type `major`, and `val` in the shortest form.
+fn write_head<S: Sink + ?Sized>(major: u8, val: u64, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+    let mut head = [0u8; 9];
+    let bytes = val.to_be_bytes();
+    let len = if val < 24 {
+        head[0] = major << 5 | val as u8;
+        1
+    } else if val <= u8::MAX as u64 {
+        head[0] = major << 5 | 24;
+        head[1..2].copy_from_slice(&bytes[7..]);
+        2
+    } else if val <= u16::MAX as u64 {
+        head[0] = major << 5 | 25;
+        head[1..3].copy_from_slice(&bytes[6..]);
+        3
+    } else if val <= u32::MAX as u64 {
+        head[0] = major << 5 | 26;
+        head[1..5].copy_from_slice(&bytes[4..]);
+        5
+    } else {
+        head[0] = major << 5 | 27;
+        head[1..9].copy_from_slice(&bytes);
+        9
+    };
+    write_all(serial, &head[..len])
+}
@@ -0,0 +1,220 @@
+//! Sparklines, bar charts and histograms of slices, drawn with Unicode block characters.
+//!
+//! ```ignore
+//! let mut chart = Chart::new().with_width(30);
+//! // "▁▂▄▆█▆▄▂▁"
+//! chart.write_sparkline_i16(&adc_samples, &mut serial)?;
+//! // "x   │██████████████▌                12.30"
+//! // "yaw │██████████████████████████████ 25.00"
+//! chart.write_bars_f32(&["x", "yaw"], &[12.3, 25.0], &mut serial)?;
+//! // "-1.00 │████████       8"
+//! // " 0.00 │███████████████ 15"
+//! chart.write_histogram_f32(&samples, 2, &mut serial)?;
+//! ```
+
+use usb_device::class_prelude::UsbError;
+
+use crate::{Sink, Writer};
+
+const SPARKS: [&str; 8] = ["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
+/// Partial blocks of 0 to 7 eighths.
+const EIGHTHS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+
+/// Range of the values mapped to the height or length of the chart.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scale {
+    /// From the minimum to the maximum of the values. Bar charts also include 0.
+    Auto,
+    /// From the first to the second value. Values out of the range are clamped.
+    Fixed(f32, f32),
+}
+
+/// Sink counting the bytes written through it, to align text before writing it.
+struct Len(usize);
+
+impl Sink for Len {
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        self.0 += data.len();
+        Ok(data.len())
+    }
+}
+
+/// Minimum and maximum of the values which are not NaN, or `None` if there are none.
+fn min_max(values: impl Iterator<Item = f32>) -> Option<(f32, f32)> {
+    values.filter(|v| !v.is_nan()).fold(None, |acc, v| match acc {
+        None => Some((v, v)),
+        Some((min, max)) => Some((if v < min { v } else { min }, if v > max { v } else { max })),
+    })
+}
+
+/// Position of `v` in `min..=max`, from 0 to 1.
+fn fraction(v: f32, min: f32, max: f32) -> f32 {
+    if max <= min {
+        return 0.0;
+    }
+    ((v - min) / (max - min)).clamp(0.0, 1.0)
+}
+
+macro_rules! sparkline {
+    ($(#[$meta: meta])*
+    $type: ty, $name: ident) => {
+        $(#[$meta])*
+        pub fn $name<S: Sink + ?Sized>(&mut self, values: &[$type], serial: &mut S) -> Result<usize, (UsbError, usize)> {
+            self.sparkline(values.iter().map(|&v| v as f32), serial)
+        }
+    };
+}
+macro_rules! bars {
+    ($(#[$meta: meta])*
+    $type: ty, $name: ident, $nodp: expr) => {
+        $(#[$meta])*
+        pub fn $name<S: Sink + ?Sized>(&mut self, labels: &[&str], values: &[$type], serial: &mut S) -> Result<usize, (UsbError, usize)> {
+            self.bars(labels, values.iter().map(|&v| v as f32), $nodp, serial)
+        }
+    };
+}
+macro_rules! histogram {
+    ($(#[$meta: meta])*
+    $type: ty, $name: ident) => {
+        $(#[$meta])*
+        pub fn $name<S: Sink + ?Sized>(&mut self, values: &[$type], bins: usize, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+            self.histogram(values.iter().map(|&v| v as f32), bins, serial)
+        }
+    };
+}
+
+/// Output structure for charts.
+pub struct Chart {
+    writer: Writer,
+    width: usize,
+    scale: Scale,
+    nodp: usize,
+}
+
+impl Chart {
+    /// Initialize Chart with bars of up to 40 columns, auto-scaling and 2 decimal places.
+    pub const fn new() -> Self {
+        Self { writer: Writer::new(), width: 40, scale: Scale::Auto, nodp: 2 }
+    }
+    /// Set the maximum number of columns of the bars of bar charts and histograms.
+    pub const fn with_width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+    /// Set the range of the values.
+    pub const fn with_scale(mut self, scale: Scale) -> Self {
+        self.scale = scale;
+        self
+    }
+    /// Set the decimal places of the values of bar charts of `f32` and the bounds of histograms.
+    pub const fn with_nodp(mut self, nodp: usize) -> Self {
+        self.nodp = nodp;
+        self
+    }
+    fn sparkline<S: Sink + ?Sized>(&mut self, values: impl Iterator<Item = f32> + Clone, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        let (min, max) = match self.scale {
+            Scale::Auto => min_max(values.clone()).unwrap_or((0.0, 0.0)),
+            Scale::Fixed(min, max) => (min, max),
+        };
+        for v in values {
+            let spark = if v.is_nan() { " " } else { SPARKS[(fraction(v, min, max) * 7.0 + 0.5) as usize] };
+            handle_err2!(self.writer.write_str(spark, serial), count);
+        }
+        handle_err2!(self.writer.write_str("\r\n", serial), count);
+        Ok(count)
+    }
+    /// Output a bar of `fraction` of the width, padded with spaces to the width.
+    fn bar<S: Sink + ?Sized>(&mut self, fraction: f32, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        let eighths = (fraction * (self.width * 8) as f32 + 0.5) as usize;
+        for _ in 0..eighths / 8 {
+            handle_err2!(self.writer.write_str("█", serial), count);
+        }
+        handle_err2!(self.writer.write_str(EIGHTHS[eighths % 8], serial), count);
+        for _ in eighths.div_ceil(8)..self.width {
+            handle_err2!(self.writer.write_str(" ", serial), count);
+        }
+        Ok(count)
+    }
+    /// Number of bytes of `val` with the decimal places of the chart.
+    fn f32_len(&mut self, val: f32) -> usize {
+        let mut len = Len(0);
+        self.writer.write_f32(val, self.nodp, &mut len).ok();
+        len.0
+    }
+    fn bars<S: Sink + ?Sized>(&mut self, labels: &[&str], values: impl Iterator<Item = f32> + Clone, nodp: Option<usize>, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        let (min, max) = match self.scale {
+            Scale::Auto => match min_max(values.clone()) {
+                Some((min, max)) => (if min < 0.0 { min } else { 0.0 }, if max > 0.0 { max } else { 0.0 }),
+                None => (0.0, 0.0),
+            },
+            Scale::Fixed(min, max) => (min, max),
+        };
+        let label_width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        for (i, v) in values.enumerate() {
+            let label = labels.get(i).copied().unwrap_or("");
+            handle_err2!(self.writer.write_str(label, serial), count);
+            for _ in label.chars().count()..label_width {
+                handle_err2!(self.writer.write_str(" ", serial), count);
+            }
+            handle_err2!(self.writer.write_str(" │", serial), count);
+            let fraction = if v.is_nan() { 0.0 } else { fraction(v, min, max) };
+            handle_err2!(self.bar(fraction, serial), count);
+            handle_err2!(self.writer.write_str(" ", serial), count);
+            handle_err2!(self.writer.write_f32(v, nodp.unwrap_or(self.nodp), serial), count);
+            handle_err2!(self.writer.write_str("\r\n", serial), count);
+        }
+        Ok(count)
+    }
+    fn histogram<S: Sink + ?Sized>(&mut self, values: impl Iterator<Item = f32> + Clone, bins: usize, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        let (min, max) = match self.scale {
+            Scale::Auto => min_max(values.clone()).unwrap_or((0.0, 0.0)),
+            Scale::Fixed(min, max) => (min, max),
+        };
+        let bin_of = |v: f32| {
+            let bin = (fraction(v, min, max) * bins as f32) as usize;
+            if bin < bins { bin } else { bins - 1 }
+        };
+        // The counts are computed bin by bin, so that no buffer is needed.
+        let bin_count = |bin: usize| values.clone().filter(|v| !v.is_nan() && bin_of(*v) == bin).count();
+        let max_count = (0..bins).map(bin_count).max().unwrap_or(0);
+        let bound = |bin: usize| min + (max - min) * bin as f32 / bins as f32;
+        // The bounds are right-aligned, measuring them first rather than formatting them into a buffer.
+        let bound_width = (0..bins).map(|bin| self.f32_len(bound(bin))).max().unwrap_or(0);
+        for bin in 0..bins {
+            for _ in self.f32_len(bound(bin))..bound_width {
+                handle_err2!(self.writer.write_str(" ", serial), count);
+            }
+            handle_err2!(self.writer.write_f32(bound(bin), self.nodp, serial), count);
+            handle_err2!(self.writer.write_str(" │", serial), count);
+            let n = bin_count(bin);
+            let fraction = if max_count == 0 { 0.0 } else { n as f32 / max_count as f32 };
+            handle_err2!(self.bar(fraction, serial), count);
+            handle_err2!(self.writer.write_str(" ", serial), count);
+            handle_err2!(self.writer.write_usize(n, serial), count);
+            handle_err2!(self.writer.write_str("\r\n", serial), count);
+        }
+        Ok(count)
+    }
+    sparkline!(/// Output `&[f32]` as a sparkline of one character per value. NaN is output as a space.
+        f32, write_sparkline_f32);
+    sparkline!(/// Output `&[i16]` as a sparkline of one character per value.
+        i16, write_sparkline_i16);
+    bars!(/// Output one bar per value of `&[f32]`, after its label and followed by the value.
+        f32, write_bars_f32, None);
+    bars!(/// Output one bar per value of `&[i16]`, after its label and followed by the value without decimal places.
+        i16, write_bars_i16, Some(0));
+    histogram!(/// Output the counts of the values of `&[f32]` in `bins` bins of equal width, with the lower bound of each bin.
+        f32, write_histogram_f32);
+    histogram!(/// Output the counts of the values of `&[i16]` in `bins` bins of equal width, with the lower bound of each bin.
+        i16, write_histogram_i16);
+}
+
+impl Default for Chart {
+    fn default() -> Self {
+        Self::new()
+    }
+}
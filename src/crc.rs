@@ -0,0 +1,77 @@
+//! CRC checksums for binary frames.
+
+/// CRC-16/X-25 (the FCS-16 of HDLC and PPP): reflected polynomial 0x1021, initial value and final XOR 0xFFFF.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Crc16 {
+    crc: u16,
+}
+
+impl Crc16 {
+    /// Initialize Crc16.
+    pub const fn new() -> Self {
+        Self { crc: 0xFFFF }
+    }
+    /// Add `data` to the checksum.
+    pub fn update(&mut self, data: &[u8]) {
+        for &b in data.iter() {
+            self.crc ^= b as u16;
+            for _ in 0..8 {
+                self.crc = if self.crc & 1 != 0 { (self.crc >> 1) ^ 0x8408 } else { self.crc >> 1 };
+            }
+        }
+    }
+    /// Checksum of the data added so far.
+    pub fn finish(&self) -> u16 {
+        !self.crc
+    }
+    /// Checksum of `data`.
+    pub fn checksum(data: &[u8]) -> u16 {
+        let mut crc = Self::new();
+        crc.update(data);
+        crc.finish()
+    }
+}
+
+impl Default for Crc16 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// CRC-32/ISO-HDLC (as used by Ethernet and zlib): reflected polynomial 0x04C11DB7, initial value and final XOR 0xFFFFFFFF.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Crc32 {
+    crc: u32,
+}
+
+impl Crc32 {
+    /// Initialize Crc32.
+    pub const fn new() -> Self {
+        Self { crc: 0xFFFF_FFFF }
+    }
+    /// Add `data` to the checksum.
+    pub fn update(&mut self, data: &[u8]) {
+        for &b in data.iter() {
+            self.crc ^= b as u32;
+            for _ in 0..8 {
+                self.crc = if self.crc & 1 != 0 { (self.crc >> 1) ^ 0xEDB8_8320 } else { self.crc >> 1 };
+            }
+        }
+    }
+    /// Checksum of the data added so far.
+    pub fn finish(&self) -> u32 {
+        !self.crc
+    }
+    /// Checksum of `data`.
+    pub fn checksum(data: &[u8]) -> u32 {
+        let mut crc = Self::new();
+        crc.update(data);
+        crc.finish()
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
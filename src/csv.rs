@@ -0,0 +1,187 @@
+//! CSV / TSV records for streaming telemetry.
+//!
+//! ```ignore
+//! let mut csv = CsvWriter::new(3);
+//! csv.write_header(&["time", "name", "temp"], &mut serial)?;
+//! csv.field_u32(1200, &mut serial)?;
+//! csv.field_str("sensor, left", &mut serial)?;
+//! csv.field_f32(23.456, 2, &mut serial)?;
+//! // "1200,\"sensor, left\",23.46\r\n"
+//! csv.end_row(&mut serial)?;
+//! ```
+
+use usb_device::class_prelude::UsbError;
+
+use crate::{Sink, Writer};
+
+/// Errors of `CsvWriter`.
+#[derive(Debug)]
+pub enum CsvError {
+    /// Error of the underlying sink.
+    Usb(UsbError),
+    /// The row already has as many fields as columns. Nothing was output.
+    TooManyFields,
+    /// The row was ended with fewer fields than columns. The missing fields were output empty.
+    TooFewFields,
+}
+
+impl From<UsbError> for CsvError {
+    fn from(e: UsbError) -> Self {
+        CsvError::Usb(e)
+    }
+}
+
+macro_rules! field_int {
+    ($(#[$meta: meta])*
+    $int: ty, $name: ident, $f: ident) => {
+        $(#[$meta])*
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: $int, serial: &mut S) -> Result<usize, (CsvError, usize)> {
+            let mut count = 0;
+            handle_err2!(self.start_field(serial), count);
+            handle_err2!(self.writer.$f(val, serial), count);
+            Ok(count)
+        }
+    };
+}
+macro_rules! field_float {
+    ($(#[$meta: meta])*
+    $float: ty, $name: ident, $f: ident) => {
+        $(#[$meta])*
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: $float, nodp: usize, serial: &mut S) -> Result<usize, (CsvError, usize)> {
+            let mut count = 0;
+            handle_err2!(self.start_field(serial), count);
+            handle_err2!(self.writer.$f(val, nodp, serial), count);
+            Ok(count)
+        }
+    };
+}
+
+/// Writer of records with a fixed number of columns.
+pub struct CsvWriter {
+    writer: Writer,
+    columns: usize,
+    field: usize,
+    delimiter: u8,
+    line_ending: &'static str,
+}
+
+impl CsvWriter {
+    /// Initialize CsvWriter for rows of `columns` fields, separated by "," and ended by "\r\n".
+    pub const fn new(columns: usize) -> Self {
+        Self { writer: Writer::new(), columns, field: 0, delimiter: b',', line_ending: "\r\n" }
+    }
+    /// Set the delimiter of fields, e.g. `b'\t'` for TSV.
+    pub const fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+    /// Set the line ending of rows, e.g. "\n".
+    pub const fn with_line_ending(mut self, line_ending: &'static str) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+    /// Number of columns.
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+    /// Number of fields output in the current row.
+    pub fn fields(&self) -> usize {
+        self.field
+    }
+    fn start_field<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (CsvError, usize)> {
+        if self.field >= self.columns {
+            return Err((CsvError::TooManyFields, 0));
+        }
+        let mut count = 0;
+        if self.field > 0 {
+            handle_err1!(serial.write(&[self.delimiter]), count);
+        }
+        self.field += 1;
+        Ok(count)
+    }
+    /// Output a header row of column names.
+    /// The number of names must be the number of columns, and no field may have been output in the current row.
+    pub fn write_header<S: Sink + ?Sized>(&mut self, names: &[&str], serial: &mut S) -> Result<usize, (CsvError, usize)> {
+        if self.field > 0 || names.len() > self.columns {
+            return Err((CsvError::TooManyFields, 0));
+        }
+        if names.len() < self.columns {
+            return Err((CsvError::TooFewFields, 0));
+        }
+        let mut count = 0;
+        for &name in names.iter() {
+            handle_err2!(self.field_str(name, serial), count);
+        }
+        handle_err2!(self.end_row(serial), count);
+        Ok(count)
+    }
+    /// Output a `&str` field.
+    /// The field is quoted if it contains the delimiter, a double quote or a line break.
+    pub fn field_str<S: Sink + ?Sized>(&mut self, val: &str, serial: &mut S) -> Result<usize, (CsvError, usize)> {
+        let mut count = 0;
+        handle_err2!(self.start_field(serial), count);
+        let quote = val.bytes().any(|b| b == self.delimiter || b == b'"' || b == b'\r' || b == b'\n');
+        if !quote {
+            handle_err2!(self.writer.write_str(val, serial), count);
+            return Ok(count);
+        }
+        handle_err2!(self.writer.write_str("\"", serial), count);
+        for (i, part) in val.split('"').enumerate() {
+            if i > 0 {
+                handle_err2!(self.writer.write_str("\"\"", serial), count);
+            }
+            handle_err2!(self.writer.write_str(part, serial), count);
+        }
+        handle_err2!(self.writer.write_str("\"", serial), count);
+        Ok(count)
+    }
+    /// Output an empty field.
+    pub fn field_empty<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (CsvError, usize)> {
+        self.start_field(serial)
+    }
+    field_int!(/// Output an `i8` field.
+        i8, field_i8, write_i8);
+    field_int!(/// Output an `i16` field.
+        i16, field_i16, write_i16);
+    field_int!(/// Output an `i32` field.
+        i32, field_i32, write_i32);
+    field_int!(/// Output an `i64` field.
+        i64, field_i64, write_i64);
+    field_int!(/// Output an `isize` field.
+        isize, field_isize, write_isize);
+    field_int!(/// Output a `u8` field.
+        u8, field_u8, write_u8);
+    field_int!(/// Output a `u16` field.
+        u16, field_u16, write_u16);
+    field_int!(/// Output a `u32` field.
+        u32, field_u32, write_u32);
+    field_int!(/// Output a `u64` field.
+        u64, field_u64, write_u64);
+    field_int!(/// Output a `usize` field.
+        usize, field_usize, write_usize);
+    field_float!(/// Output an `f32` field to `nodp` decimal places.
+        f32, field_f32, write_f32);
+    field_float!(/// Output an `f64` field to `nodp` decimal places.
+        f64, field_f64, write_f64);
+    field_float!(/// Output an `f32` field to `nodp` decimal places in exponential format.
+        f32, field_f32_exp, write_f32_exp);
+    field_float!(/// Output an `f64` field to `nodp` decimal places in exponential format.
+        f64, field_f64_exp, write_f64_exp);
+    /// End the current row.
+    ///
+    /// If the row has fewer fields than columns, the missing fields are output empty
+    /// so that the output stays consistent, and `CsvError::TooFewFields` is returned.
+    pub fn end_row<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (CsvError, usize)> {
+        let mut count = 0;
+        let missing = self.field < self.columns;
+        while self.field < self.columns {
+            handle_err2!(self.start_field(serial), count);
+        }
+        handle_err2!(self.writer.write_str(self.line_ending, serial), count);
+        self.field = 0;
+        if missing {
+            return Err((CsvError::TooFewFields, count));
+        }
+        Ok(count)
+    }
+}
@@ -0,0 +1,172 @@
+//! Live dashboard of labelled values at fixed positions of a terminal.
+//!
+//! Values are formatted when they are set, and `draw` moves the cursor with ANSI sequences to redraw
+//! only the values whose text has changed. It needs the `ansi` feature.
+//!
+//! ```ignore
+//! let mut dash = Dashboard::<16, 12>::new();
+//! let temp = dash.add_field(1, 1, "temp: ").unwrap();
+//! let state = dash.add_field(2, 1, "state: ").unwrap();
+//! loop {
+//!     dash.set_f32(temp, read_temp(), 2);
+//!     dash.set_str(state, if armed { "armed" } else { "idle" });
+//!     // The first call clears the screen and draws the labels.
+//!     dash.draw(&mut serial).ok();
+//! }
+//! ```
+
+use usb_device::class_prelude::UsbError;
+
+use crate::{SliceSink, Sink, Writer};
+
+/// Errors of `Dashboard`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DashboardError {
+    /// All the fields of `Dashboard` are used.
+    Full,
+}
+
+/// Handle of a field returned by `Dashboard::add_field`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FieldId(usize);
+
+#[derive(Clone, Copy)]
+struct Field<const W: usize> {
+    row: u16,
+    col: u16,
+    label: &'static str,
+    value: [u8; W],
+    len: usize,
+    /// Length of the value on the screen, to erase the rest of a longer previous value.
+    drawn: usize,
+    dirty: bool,
+}
+
+macro_rules! set_int {
+    ($(#[$meta: meta])*
+    $int: ty, $name: ident, $f: ident) => {
+        $(#[$meta])*
+        pub fn $name(&mut self, id: FieldId, val: $int) {
+            let mut buf = [0u8; W];
+            let mut sink = SliceSink::new(&mut buf);
+            self.writer.$f(val, &mut sink).ok();
+            let len = sink.len();
+            self.update(id, &buf[..len]);
+        }
+    };
+}
+macro_rules! set_float {
+    ($(#[$meta: meta])*
+    $float: ty, $name: ident, $f: ident) => {
+        $(#[$meta])*
+        pub fn $name(&mut self, id: FieldId, val: $float, nodp: usize) {
+            let mut buf = [0u8; W];
+            let mut sink = SliceSink::new(&mut buf);
+            self.writer.$f(val, nodp, &mut sink).ok();
+            let len = sink.len();
+            self.update(id, &buf[..len]);
+        }
+    };
+}
+
+/// Dashboard of up to `F` fields, each with a value of up to `W` bytes.
+/// Longer values are cut off.
+pub struct Dashboard<const F: usize, const W: usize> {
+    fields: [Field<W>; F],
+    count: usize,
+    writer: Writer,
+    /// The screen must be cleared and the labels drawn.
+    full: bool,
+}
+
+impl<const F: usize, const W: usize> Dashboard<F, W> {
+    /// Initialize Dashboard.
+    pub const fn new() -> Self {
+        Self {
+            fields: [Field { row: 0, col: 0, label: "", value: [0u8; W], len: 0, drawn: 0, dirty: false }; F],
+            count: 0,
+            writer: Writer::new(),
+            full: true,
+        }
+    }
+    /// Add a field with `label` at `row` and `col` (both starting from 1). The value is drawn after the label.
+    pub fn add_field(&mut self, row: u16, col: u16, label: &'static str) -> Result<FieldId, DashboardError> {
+        if self.count >= F {
+            return Err(DashboardError::Full);
+        }
+        self.fields[self.count] = Field { row, col, label, value: [0u8; W], len: 0, drawn: 0, dirty: false };
+        self.count += 1;
+        self.full = true;
+        Ok(FieldId(self.count - 1))
+    }
+    /// Redraw the whole screen at the next `draw`, e.g. after a terminal is connected.
+    pub fn invalidate(&mut self) {
+        self.full = true;
+    }
+    fn update(&mut self, id: FieldId, text: &[u8]) {
+        let field = &mut self.fields[id.0];
+        if field.value[..field.len] != *text {
+            field.value[..text.len()].copy_from_slice(text);
+            field.len = text.len();
+            field.dirty = true;
+        }
+    }
+    /// Set the value of a `&str` field.
+    pub fn set_str(&mut self, id: FieldId, val: &str) {
+        let mut len = val.len().min(W);
+        while !val.is_char_boundary(len) {
+            len -= 1;
+        }
+        self.update(id, &val.as_bytes()[..len]);
+    }
+    set_int!(/// Set the value of an `i32` field.
+        i32, set_i32, write_i32);
+    set_int!(/// Set the value of an `i64` field.
+        i64, set_i64, write_i64);
+    set_int!(/// Set the value of a `u32` field.
+        u32, set_u32, write_u32);
+    set_int!(/// Set the value of a `u64` field.
+        u64, set_u64, write_u64);
+    set_float!(/// Set the value of an `f32` field to `nodp` decimal places.
+        f32, set_f32, write_f32);
+    set_float!(/// Set the value of an `f64` field to `nodp` decimal places.
+        f64, set_f64, write_f64);
+    /// Output the changed values, or the whole dashboard after `new`, `add_field` or `invalidate`.
+    ///
+    /// What could not be output completely is drawn again at the next call.
+    pub fn draw<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        let full = self.full;
+        if full {
+            handle_err2!(self.writer.write_clear_screen(serial), count);
+        }
+        for field in self.fields[..self.count].iter_mut() {
+            if full {
+                field.drawn = 0;
+                handle_err2!(self.writer.write_cursor_to(field.row, field.col, serial), count);
+                handle_err2!(self.writer.write_str(field.label, serial), count);
+            } else if field.dirty {
+                let col = field.col.saturating_add(u16::try_from(field.label.chars().count()).unwrap_or(u16::MAX));
+                handle_err2!(self.writer.write_cursor_to(field.row, col, serial), count);
+            } else {
+                continue;
+            }
+            let value = core::str::from_utf8(&field.value[..field.len]).unwrap_or("");
+            handle_err2!(self.writer.write_str(value, serial), count);
+            let width = value.chars().count();
+            for _ in width..field.drawn {
+                handle_err2!(self.writer.write_str(" ", serial), count);
+            }
+            field.drawn = width;
+            field.dirty = false;
+        }
+        self.full = false;
+        Ok(count)
+    }
+}
+
+impl<const F: usize, const W: usize> Default for Dashboard<F, W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
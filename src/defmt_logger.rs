@@ -0,0 +1,111 @@
+//! `defmt` global logger over USB CDC (requires the `defmt` feature).
+//!
+//! defmt frames are rzCOBS encoded into an internal buffer of `BUFFER_SIZE` bytes,
+//! and sent to the serial port by `drain`, e.g. after `usb_dev.poll`.
+//! On the host, the stream can be decoded with `cat /dev/ttyACM0 | defmt-print -e <elf>`.
+//!
+//! ```ignore
+//! use serial_write::defmt_logger;
+//!
+//! defmt::info!("started");
+//! loop {
+//!     let _ = usb_dev.poll(&mut [&mut serial]);
+//!     let _ = defmt_logger::drain(&mut serial);
+//! }
+//! ```
+
+use core::cell::RefCell;
+use critical_section::{Mutex, RestoreState};
+use usb_device::class_prelude::UsbError;
+
+use crate::{RingBuffer, Sink};
+
+/// Number of bytes of encoded frames which can wait for `drain`.
+pub const BUFFER_SIZE: usize = 1024;
+
+struct State {
+    encoder: defmt::Encoder,
+    buf: RingBuffer<BUFFER_SIZE>,
+    taken: bool,
+    restore: RestoreState,
+    /// Length of `buf` when the current frame started.
+    start: usize,
+    /// Whether some bytes of the current frame did not fit in `buf`.
+    overflow: bool,
+    dropped: usize,
+}
+
+static STATE: Mutex<RefCell<State>> = Mutex::new(RefCell::new(State {
+    encoder: defmt::Encoder::new(),
+    buf: RingBuffer::new(),
+    taken: false,
+    restore: RestoreState::invalid(),
+    start: 0,
+    overflow: false,
+    dropped: 0,
+}));
+
+/// Output the buffered frames to `serial`.
+/// Returns the number of bytes output; the rest is kept until the next call.
+pub fn drain<S: Sink + ?Sized>(serial: &mut S) -> Result<usize, (UsbError, usize)> {
+    critical_section::with(|cs| STATE.borrow_ref_mut(cs).buf.drain(serial))
+}
+
+/// Number of frames discarded so far because the buffer was full.
+pub fn dropped() -> usize {
+    critical_section::with(|cs| STATE.borrow_ref(cs).dropped)
+}
+
+fn encode(f: impl FnOnce(&mut defmt::Encoder, &mut dyn FnMut(&[u8]))) {
+    critical_section::with(|cs| {
+        let mut state = STATE.borrow_ref_mut(cs);
+        let State { encoder, buf, overflow, .. } = &mut *state;
+        f(encoder, &mut |bytes| {
+            if buf.push(bytes) < bytes.len() {
+                *overflow = true;
+            }
+        });
+    });
+}
+
+#[defmt::global_logger]
+struct Logger;
+
+unsafe impl defmt::Logger for Logger {
+    fn acquire() {
+        // Released in `release`.
+        let restore = unsafe { critical_section::acquire() };
+        critical_section::with(|cs| {
+            let mut state = STATE.borrow_ref_mut(cs);
+            if state.taken {
+                panic!("defmt logger taken reentrantly");
+            }
+            state.taken = true;
+            state.restore = restore;
+            state.start = state.buf.len();
+            state.overflow = false;
+        });
+        encode(|encoder, write| encoder.start_frame(write));
+    }
+    unsafe fn flush() {
+        // Frames are sent by `drain`, which needs the USB device to be polled.
+    }
+    unsafe fn release() {
+        encode(|encoder, write| encoder.end_frame(write));
+        let restore = critical_section::with(|cs| {
+            let mut state = STATE.borrow_ref_mut(cs);
+            if state.overflow {
+                // Never leave a partial frame in the buffer.
+                let start = state.start;
+                state.buf.truncate(start);
+                state.dropped += 1;
+            }
+            state.taken = false;
+            state.restore
+        });
+        unsafe { critical_section::release(restore) };
+    }
+    unsafe fn write(bytes: &[u8]) {
+        encode(|encoder, write| encoder.write(bytes, write));
+    }
+}
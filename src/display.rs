@@ -0,0 +1,346 @@
+//! Sinks drawing the output of `Writer` on a display, for devices without a USB connection.
+//!
+//! The text is kept in a grid of `COLS` x `ROWS` characters.
+//! Lines longer than `COLS` wrap, "\r" returns to the start of the line (so `ProgressBar` redraws in place),
+//! and "\n" starts a new line. The new line only starts at the next character, so that `writeln_*` does not
+//! leave the last row empty; past the last row, the text scrolls up by a row. ANSI escape sequences are skipped.
+//!
+//! ```ignore
+//! // HD44780 with 16 x 2 characters, through its driver.
+//! let mut lcd = CharLcd::<_, 16, 2>::new(Hd44780(lcd, delay));
+//! writer.write_str("T: ", &mut lcd).ok();
+//! writer.writeln_f32(temp, 1, &mut lcd).ok();
+//!
+//! // SSD1306 with 128 x 64 pixels, as 21 x 6 characters of 6 x 10 pixels.
+//! let mut console = TextConsole::<_, 21, 6>::new(oled, &FONT_6X10, BinaryColor::On, BinaryColor::Off);
+//! writer.writeln_f32(temp, 1, &mut console).ok();
+//! console.target_mut().flush().ok();
+//! ```
+//!
+//! Errors of the display are returned as `UsbError::InvalidState`. The text is kept, and drawn again by `redraw`.
+
+use usb_device::class_prelude::UsbError;
+
+use crate::Sink;
+
+/// State of an ANSI escape sequence being skipped.
+#[derive(Clone, Copy, PartialEq)]
+enum Escape {
+    None,
+    /// After "\x1b".
+    Esc,
+    /// After "\x1b[", up to the final byte.
+    Csi,
+}
+
+/// Characters on the display, and the position of the next one.
+struct Grid<const COLS: usize, const ROWS: usize> {
+    cells: [[char; COLS]; ROWS],
+    row: usize,
+    /// Column of the next character, `COLS` if it wraps to the next row.
+    col: usize,
+    /// Whether a new line starts at the next character.
+    newline: bool,
+    /// Bytes of an incomplete UTF-8 character.
+    utf8: [u8; 4],
+    utf8_len: usize,
+    escape: Escape,
+}
+
+impl<const COLS: usize, const ROWS: usize> Grid<COLS, ROWS> {
+    const fn new() -> Self {
+        Self {
+            cells: [[' '; COLS]; ROWS],
+            row: 0,
+            col: 0,
+            newline: false,
+            utf8: [0; 4],
+            utf8_len: 0,
+            escape: Escape::None,
+        }
+    }
+    fn clear(&mut self) {
+        *self = Self::new();
+    }
+    /// Put `data` into the grid, calling `draw` with the row, column and character of each cell changed.
+    fn write<E>(&mut self, data: &[u8], mut draw: impl FnMut(usize, usize, char) -> Result<(), E>) -> Result<usize, UsbError> {
+        for &b in data {
+            if let Some(c) = self.decode(b) {
+                match self.put(c) {
+                    Some(true) => self.draw_all(&mut draw),
+                    Some(false) => draw(self.row, self.col - 1, c),
+                    None => Ok(()),
+                }
+                .map_err(|_| UsbError::InvalidState)?;
+            }
+        }
+        Ok(data.len())
+    }
+    fn draw_all<E>(&self, draw: &mut impl FnMut(usize, usize, char) -> Result<(), E>) -> Result<(), E> {
+        for (row, line) in self.cells.iter().enumerate() {
+            for (col, &c) in line.iter().enumerate() {
+                draw(row, col, c)?;
+            }
+        }
+        Ok(())
+    }
+    /// Character completed by `b`, skipping escape sequences.
+    fn decode(&mut self, b: u8) -> Option<char> {
+        match self.escape {
+            Escape::Esc => {
+                self.escape = if b == b'[' { Escape::Csi } else { Escape::None };
+                return None;
+            }
+            Escape::Csi => {
+                if (0x40..=0x7E).contains(&b) {
+                    self.escape = Escape::None;
+                }
+                return None;
+            }
+            Escape::None => {}
+        }
+        if self.utf8_len > 0 && b & 0xC0 == 0x80 {
+            self.utf8[self.utf8_len] = b;
+            self.utf8_len += 1;
+            let len = match self.utf8[0] {
+                0xF0.. => 4,
+                0xE0.. => 3,
+                _ => 2,
+            };
+            if self.utf8_len < len {
+                return None;
+            }
+            self.utf8_len = 0;
+            let c = core::str::from_utf8(&self.utf8[..len]).ok().and_then(|s| s.chars().next());
+            return Some(c.unwrap_or(char::REPLACEMENT_CHARACTER));
+        }
+        // A character cut off by this byte is skipped.
+        self.utf8_len = 0;
+        match b {
+            0x1B => {
+                self.escape = Escape::Esc;
+                None
+            }
+            0x00..=0x7F => Some(b as char),
+            0xC0.. => {
+                self.utf8[0] = b;
+                self.utf8_len = 1;
+                None
+            }
+            _ => Some(char::REPLACEMENT_CHARACTER),
+        }
+    }
+    /// Put `c` at the next position.
+    /// Returns `Some(true)` if every cell must be drawn, `Some(false)` if the cell before the next position.
+    fn put(&mut self, c: char) -> Option<bool> {
+        match c {
+            '\r' => {
+                self.col = 0;
+                None
+            }
+            '\n' => {
+                // An empty line if a new line is already due.
+                let scrolled = self.newline && self.line_feed();
+                self.newline = true;
+                if scrolled { Some(true) } else { None }
+            }
+            c if c.is_control() => None,
+            c => {
+                let scrolled = (self.newline || self.col == COLS) && self.line_feed();
+                self.cells[self.row][self.col] = c;
+                self.col += 1;
+                Some(scrolled)
+            }
+        }
+    }
+    /// Move to the start of the next row, and return `true` if the text scrolled up.
+    fn line_feed(&mut self) -> bool {
+        self.newline = false;
+        self.col = 0;
+        if self.row + 1 < ROWS {
+            self.row += 1;
+            return false;
+        }
+        self.cells.rotate_left(1);
+        self.cells[ROWS - 1] = [' '; COLS];
+        true
+    }
+}
+
+/// Character display such as HD44780, implemented over its driver.
+///
+/// ```ignore
+/// struct Hd44780<B, D>(HD44780<B>, D);
+///
+/// impl<B: DataBus, D: DelayUs<u16> + DelayMs<u8>> CharDisplay for Hd44780<B, D> {
+///     type Error = hd44780_driver::error::Error;
+///     fn set_cursor(&mut self, col: u8, row: u8) -> Result<(), Self::Error> {
+///         // DDRAM addresses of the rows of a 20 x 4 display.
+///         self.0.set_cursor_pos([0x00, 0x40, 0x14, 0x54][row as usize] + col, &mut self.1)
+///     }
+///     fn write_char(&mut self, c: char) -> Result<(), Self::Error> {
+///         match c {
+///             '°' => self.0.write_byte(0xDF, &mut self.1),
+///             ' '..='}' => self.0.write_char(c, &mut self.1),
+///             _ => self.0.write_char('?', &mut self.1),
+///         }
+///     }
+/// }
+/// ```
+pub trait CharDisplay {
+    type Error;
+    /// Move the cursor to `col` of `row`, both from 0.
+    fn set_cursor(&mut self, col: u8, row: u8) -> Result<(), Self::Error>;
+    /// Write `c` at the cursor, and move the cursor to the right.
+    ///
+    /// Characters which are not in the character ROM should be replaced, e.g. by "?".
+    fn write_char(&mut self, c: char) -> Result<(), Self::Error>;
+}
+
+/// Sink drawing text on a character display of `COLS` x `ROWS` characters.
+///
+/// Only the characters changed are written, and the cursor is only moved when it is not already at the cell.
+pub struct CharLcd<D: CharDisplay, const COLS: usize, const ROWS: usize> {
+    display: D,
+    grid: Grid<COLS, ROWS>,
+    /// Position of the cursor of the display, if known.
+    cursor: Option<(usize, usize)>,
+}
+
+impl<D: CharDisplay, const COLS: usize, const ROWS: usize> CharLcd<D, COLS, ROWS> {
+    /// The cursor is moved with `u8` positions, so empty or larger sizes fail to compile.
+    const VALID_SIZE: () = assert!(
+        COLS > 0 && ROWS > 0 && COLS <= 255 && ROWS <= 255,
+        "CharLcd has 1 to 255 columns and rows"
+    );
+
+    /// Initialize CharLcd on a cleared `display`.
+    pub const fn new(display: D) -> Self {
+        let () = Self::VALID_SIZE;
+        Self { display, grid: Grid::new(), cursor: None }
+    }
+    /// Clear the text, and start again from the top left.
+    pub fn clear(&mut self) -> Result<(), D::Error> {
+        self.grid.clear();
+        self.redraw()
+    }
+    /// Draw every character again, e.g. after the display is reset.
+    pub fn redraw(&mut self) -> Result<(), D::Error> {
+        let Self { display, grid, cursor } = self;
+        *cursor = None;
+        grid.draw_all(&mut |row, col, c| draw_char::<D, COLS>(display, cursor, row, col, c))
+    }
+    /// Display.
+    pub fn display(&self) -> &D {
+        &self.display
+    }
+    /// Display, e.g. to turn on the backlight.
+    pub fn display_mut(&mut self) -> &mut D {
+        &mut self.display
+    }
+    /// Release the display.
+    pub fn into_inner(self) -> D {
+        self.display
+    }
+}
+
+/// Write `c` at `col` of `row`, moving the cursor unless it is already there.
+fn draw_char<D: CharDisplay, const COLS: usize>(display: &mut D, cursor: &mut Option<(usize, usize)>, row: usize, col: usize, c: char) -> Result<(), D::Error> {
+    if *cursor != Some((row, col)) {
+        *cursor = None;
+        display.set_cursor(col as u8, row as u8)?;
+    }
+    display.write_char(c)?;
+    // The cursor of HD44780 does not move to the next row in order.
+    *cursor = if col + 1 < COLS { Some((row, col + 1)) } else { None };
+    Ok(())
+}
+
+impl<D: CharDisplay, const COLS: usize, const ROWS: usize> Sink for CharLcd<D, COLS, ROWS> {
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        let Self { display, grid, cursor } = self;
+        grid.write(data, |row, col, c| {
+            draw_char::<D, COLS>(display, cursor, row, col, c).inspect_err(|_| *cursor = None)
+        })
+    }
+}
+
+#[cfg(feature = "embedded-graphics")]
+pub use self::graphics::TextConsole;
+
+#[cfg(feature = "embedded-graphics")]
+mod graphics {
+    use embedded_graphics::mono_font::{MonoFont, MonoTextStyle, MonoTextStyleBuilder};
+    use embedded_graphics::prelude::*;
+    use embedded_graphics::text::{Baseline, Text};
+    use usb_device::class_prelude::UsbError;
+
+    use super::Grid;
+    use crate::Sink;
+
+    /// Sink drawing text of `COLS` x `ROWS` characters in a mono font on an `embedded-graphics` `DrawTarget`.
+    ///
+    /// Each character is drawn with its background, so that no clearing is needed.
+    /// For displays with a frame buffer, the buffer must still be flushed through `target_mut`.
+    pub struct TextConsole<'f, D: DrawTarget, const COLS: usize, const ROWS: usize> {
+        target: D,
+        style: MonoTextStyle<'f, D::Color>,
+        origin: Point,
+        grid: Grid<COLS, ROWS>,
+    }
+
+    impl<'f, D: DrawTarget, const COLS: usize, const ROWS: usize> TextConsole<'f, D, COLS, ROWS> {
+        /// An empty console fails to compile.
+        const VALID_SIZE: () = assert!(COLS > 0 && ROWS > 0, "TextConsole has at least 1 column and row");
+
+        /// Initialize TextConsole drawing with `font` in `color` on `background`, from the top left of `target`.
+        pub fn new(target: D, font: &'f MonoFont<'f>, color: D::Color, background: D::Color) -> Self {
+            let () = Self::VALID_SIZE;
+            let style = MonoTextStyleBuilder::new().font(font).text_color(color).background_color(background).build();
+            Self { target, style, origin: Point::zero(), grid: Grid::new() }
+        }
+        /// Set the top left of the text, e.g. below a title.
+        pub fn with_origin(mut self, origin: Point) -> Self {
+            self.origin = origin;
+            self
+        }
+        /// Clear the text, and start again from the top left.
+        pub fn clear(&mut self) -> Result<(), D::Error> {
+            self.grid.clear();
+            self.redraw()
+        }
+        /// Draw every character again, e.g. after other drawing on the target.
+        pub fn redraw(&mut self) -> Result<(), D::Error> {
+            let Self { target, style, origin, grid } = self;
+            grid.draw_all(&mut |row, col, c| draw_char(target, style, *origin, row, col, c))
+        }
+        /// Target.
+        pub fn target(&self) -> &D {
+            &self.target
+        }
+        /// Target, e.g. to flush its frame buffer.
+        pub fn target_mut(&mut self) -> &mut D {
+            &mut self.target
+        }
+        /// Release the target.
+        pub fn into_inner(self) -> D {
+            self.target
+        }
+    }
+
+    /// Draw `c` in the cell at `col` of `row`.
+    fn draw_char<D: DrawTarget>(target: &mut D, style: &MonoTextStyle<D::Color>, origin: Point, row: usize, col: usize, c: char) -> Result<(), D::Error> {
+        let size = style.font.character_size;
+        let offset = Point::new(col as i32 * (size.width + style.font.character_spacing) as i32, row as i32 * size.height as i32);
+        let mut buf = [0u8; 4];
+        Text::with_baseline(c.encode_utf8(&mut buf), origin + offset, *style, Baseline::Top).draw(target)?;
+        Ok(())
+    }
+
+    impl<D: DrawTarget, const COLS: usize, const ROWS: usize> Sink for TextConsole<'_, D, COLS, ROWS> {
+        fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+            let Self { target, style, origin, grid } = self;
+            grid.write(data, |row, col, c| draw_char(target, style, *origin, row, col, c))
+        }
+    }
+}
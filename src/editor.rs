@@ -0,0 +1,478 @@
+//! Line editing for terminals (VT100 / xterm key sequences), with history and tab completion.
+//!
+//! Supported keys: Left / Right, Home / End (also Ctrl-A / Ctrl-E), Backspace, Delete (also Ctrl-D),
+//! Up / Down through the history, Tab to complete the first word, Ctrl-U to clear the line and Ctrl-C to discard it.
+//! The sequences sent by PuTTY, minicom and `screen` are understood.
+//!
+//! ```ignore
+//! let mut writer = Writer::new();
+//! let mut editor = LineEditor::<64, 8>::new().with_prompt("imu> ");
+//! let mut shell = Shell::<64, 8>::new(&COMMANDS);
+//! editor.write_prompt(&mut writer, &mut serial).ok();
+//! loop {
+//!     if !usb_dev.poll(&mut [&mut serial]) {
+//!         continue;
+//!     }
+//!     if let Ok(Some(line)) = editor.read_line(&mut writer, &mut serial, &COMMANDS[..]) {
+//!         shell.execute(line, &mut serial).ok();
+//!         editor.write_prompt(&mut writer, &mut serial).ok();
+//!     }
+//! }
+//! ```
+
+use usb_device::class_prelude::UsbError;
+
+use crate::input::ReadError;
+use crate::{Sink, Source, Writer};
+
+/// Candidates of tab completion.
+pub trait Completions {
+    /// The `index`-th candidate, or `None` after the last one.
+    fn candidate(&self, index: usize) -> Option<&str>;
+}
+
+impl Completions for [&str] {
+    fn candidate(&self, index: usize) -> Option<&str> {
+        self.get(index).copied()
+    }
+}
+
+/// State of the escape sequence being received.
+#[derive(Clone, Copy)]
+enum Escape {
+    None,
+    /// ESC was received.
+    Esc,
+    /// ESC [ was received, with the numeric parameter so far.
+    Csi(u8),
+    /// ESC O was received.
+    Ss3,
+}
+
+/// Editor of lines of up to `N` bytes, remembering the last `H` lines.
+pub struct LineEditor<'a, const N: usize, const H: usize> {
+    buf: [u8; N],
+    len: usize,
+    /// Byte position of the cursor, always at a character boundary.
+    cursor: usize,
+    history: [[u8; N]; H],
+    history_len: [usize; H],
+    /// Number of lines in the history.
+    history_count: usize,
+    /// Slot of the next line in the history.
+    history_head: usize,
+    /// Position in the history being shown, 0 being the newest line.
+    browsing: Option<usize>,
+    /// Line being edited before browsing the history.
+    draft: [u8; N],
+    draft_len: usize,
+    /// Bytes of a multi-byte character being received.
+    utf8: [u8; 4],
+    utf8_len: usize,
+    escape: Escape,
+    last_cr: bool,
+    /// A line was returned; it is cleared at the next read.
+    ready: bool,
+    prompt: &'a str,
+}
+
+/// Number of characters of UTF-8 `bytes`.
+fn chars(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&b| b & 0xC0 != 0x80).count()
+}
+
+fn as_str(bytes: &[u8]) -> &str {
+    core::str::from_utf8(bytes).unwrap_or("")
+}
+
+/// Length of the UTF-8 character starting with `b`.
+fn utf8_width(b: u8) -> usize {
+    match b {
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF7 => 4,
+        _ => 1,
+    }
+}
+
+/// Output `s`. The echo is best effort, so errors are ignored.
+fn out<S: Sink + ?Sized>(writer: &mut Writer, s: &str, serial: &mut S) {
+    writer.write_str(s, serial).ok();
+}
+
+/// Move the cursor of the terminal `n` columns to the left.
+fn move_left<S: Sink + ?Sized>(writer: &mut Writer, n: usize, serial: &mut S) {
+    if n == 0 {
+        return;
+    }
+    out(writer, "\x1b[", serial);
+    writer.write_usize(n, serial).ok();
+    out(writer, "D", serial);
+}
+
+impl<'a, const N: usize, const H: usize> LineEditor<'a, N, H> {
+    /// Initialize LineEditor with the prompt "> ".
+    pub const fn new() -> Self {
+        Self {
+            buf: [0u8; N],
+            len: 0,
+            cursor: 0,
+            history: [[0u8; N]; H],
+            history_len: [0; H],
+            history_count: 0,
+            history_head: 0,
+            browsing: None,
+            draft: [0u8; N],
+            draft_len: 0,
+            utf8: [0u8; 4],
+            utf8_len: 0,
+            escape: Escape::None,
+            last_cr: false,
+            ready: false,
+            prompt: "> ",
+        }
+    }
+    /// Set the prompt, output by `write_prompt` and when the line is redrawn.
+    pub const fn with_prompt(mut self, prompt: &'a str) -> Self {
+        self.prompt = prompt;
+        self
+    }
+    /// Output the prompt.
+    pub fn write_prompt<S: Sink + ?Sized>(&mut self, writer: &mut Writer, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        writer.write_str(self.prompt, serial)
+    }
+    /// Discard the current line, without output.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.cursor = 0;
+        self.browsing = None;
+        self.utf8_len = 0;
+        self.escape = Escape::None;
+        self.ready = false;
+    }
+    /// The `index`-th newest line of the history.
+    pub fn history(&self, index: usize) -> Option<&str> {
+        if index >= self.history_count {
+            return None;
+        }
+        let slot = (self.history_head + H - 1 - index) % H;
+        Some(as_str(&self.history[slot][..self.history_len[slot]]))
+    }
+    fn push_history(&mut self) {
+        if H == 0 || self.len == 0 || self.history(0).map(|s| s.as_bytes()) == Some(&self.buf[..self.len]) {
+            return;
+        }
+        let slot = self.history_head;
+        self.history[slot][..self.len].copy_from_slice(&self.buf[..self.len]);
+        self.history_len[slot] = self.len;
+        self.history_head = (slot + 1) % H;
+        self.history_count = (self.history_count + 1).min(H);
+    }
+    /// Insert `bytes` at the cursor. The line is left unchanged if they do not fit.
+    fn insert<S: Sink + ?Sized>(&mut self, bytes: &[u8], writer: &mut Writer, serial: &mut S) {
+        let k = bytes.len();
+        if self.len + k > N {
+            out(writer, "\x07", serial);
+            return;
+        }
+        self.buf.copy_within(self.cursor..self.len, self.cursor + k);
+        self.buf[self.cursor..self.cursor + k].copy_from_slice(bytes);
+        self.len += k;
+        out(writer, as_str(&self.buf[self.cursor..self.len]), serial);
+        self.cursor += k;
+        move_left(writer, chars(&self.buf[self.cursor..self.len]), serial);
+    }
+    /// Remove the bytes `start..end` of the line, with the cursor at `start` on the terminal.
+    fn remove<S: Sink + ?Sized>(&mut self, start: usize, end: usize, writer: &mut Writer, serial: &mut S) {
+        self.buf.copy_within(end..self.len, start);
+        self.len -= end - start;
+        self.cursor = start;
+        out(writer, as_str(&self.buf[start..self.len]), serial);
+        out(writer, "\x1b[K", serial);
+        move_left(writer, chars(&self.buf[start..self.len]), serial);
+    }
+    fn prev_boundary(&self, i: usize) -> usize {
+        let mut i = i;
+        while i > 0 {
+            i -= 1;
+            if self.buf[i] & 0xC0 != 0x80 {
+                break;
+            }
+        }
+        i
+    }
+    fn next_boundary(&self, i: usize) -> usize {
+        let mut i = i + 1;
+        while i < self.len && self.buf[i] & 0xC0 == 0x80 {
+            i += 1;
+        }
+        i
+    }
+    fn backspace<S: Sink + ?Sized>(&mut self, writer: &mut Writer, serial: &mut S) {
+        if self.cursor == 0 {
+            return;
+        }
+        let start = self.prev_boundary(self.cursor);
+        out(writer, "\x08", serial);
+        self.remove(start, self.cursor, writer, serial);
+    }
+    fn delete<S: Sink + ?Sized>(&mut self, writer: &mut Writer, serial: &mut S) {
+        if self.cursor == self.len {
+            return;
+        }
+        let end = self.next_boundary(self.cursor);
+        self.remove(self.cursor, end, writer, serial);
+    }
+    fn left<S: Sink + ?Sized>(&mut self, writer: &mut Writer, serial: &mut S) {
+        if self.cursor > 0 {
+            self.cursor = self.prev_boundary(self.cursor);
+            out(writer, "\x1b[D", serial);
+        }
+    }
+    fn right<S: Sink + ?Sized>(&mut self, writer: &mut Writer, serial: &mut S) {
+        if self.cursor < self.len {
+            self.cursor = self.next_boundary(self.cursor);
+            out(writer, "\x1b[C", serial);
+        }
+    }
+    fn home<S: Sink + ?Sized>(&mut self, writer: &mut Writer, serial: &mut S) {
+        move_left(writer, chars(&self.buf[..self.cursor]), serial);
+        self.cursor = 0;
+    }
+    fn end<S: Sink + ?Sized>(&mut self, writer: &mut Writer, serial: &mut S) {
+        out(writer, as_str(&self.buf[self.cursor..self.len]), serial);
+        self.cursor = self.len;
+    }
+    /// Replace the whole line by `bytes` (at most `N` bytes), with the cursor at the end.
+    fn replace<S: Sink + ?Sized>(&mut self, bytes: &[u8], writer: &mut Writer, serial: &mut S) {
+        self.home(writer, serial);
+        self.buf[..bytes.len()].copy_from_slice(bytes);
+        self.len = bytes.len();
+        self.cursor = self.len;
+        out(writer, as_str(&self.buf[..self.len]), serial);
+        out(writer, "\x1b[K", serial);
+    }
+    fn up<S: Sink + ?Sized>(&mut self, writer: &mut Writer, serial: &mut S) {
+        let index = self.browsing.map_or(0, |i| i + 1);
+        if index >= self.history_count {
+            out(writer, "\x07", serial);
+            return;
+        }
+        if self.browsing.is_none() {
+            self.draft[..self.len].copy_from_slice(&self.buf[..self.len]);
+            self.draft_len = self.len;
+        }
+        self.browsing = Some(index);
+        let slot = (self.history_head + H - 1 - index) % H;
+        let entry = self.history[slot];
+        self.replace(&entry[..self.history_len[slot]], writer, serial);
+    }
+    fn down<S: Sink + ?Sized>(&mut self, writer: &mut Writer, serial: &mut S) {
+        match self.browsing {
+            None => { out(writer, "\x07", serial); }
+            Some(0) => {
+                self.browsing = None;
+                let draft = self.draft;
+                self.replace(&draft[..self.draft_len], writer, serial);
+            }
+            Some(index) => {
+                self.browsing = Some(index - 1);
+                let slot = (self.history_head + H - index) % H;
+                let entry = self.history[slot];
+                self.replace(&entry[..self.history_len[slot]], writer, serial);
+            }
+        }
+    }
+    /// Complete the first word at the end of the line from `completions`.
+    ///
+    /// A unique candidate is completed with a trailing space, several candidates are completed up to
+    /// their common prefix, and listed if there is nothing to complete.
+    fn complete<S: Sink + ?Sized, C: Completions + ?Sized>(&mut self, completions: &C, writer: &mut Writer, serial: &mut S) {
+        let prefix = &self.buf[..self.len];
+        if self.cursor != self.len || prefix.iter().any(|b| b.is_ascii_whitespace()) {
+            out(writer, "\x07", serial);
+            return;
+        }
+        let mut first: Option<&str> = None;
+        let mut common = 0;
+        let mut matches = 0;
+        let mut i = 0;
+        while let Some(c) = completions.candidate(i) {
+            i += 1;
+            if !c.as_bytes().starts_with(prefix) {
+                continue;
+            }
+            matches += 1;
+            match first {
+                None => {
+                    first = Some(c);
+                    common = c.len();
+                }
+                Some(f) => {
+                    common = f.bytes().zip(c.bytes()).take(common).take_while(|(a, b)| a == b).count();
+                    while !f.is_char_boundary(common) {
+                        common -= 1;
+                    }
+                }
+            }
+        }
+        let first = match first {
+            Some(first) => first,
+            None => {
+                out(writer, "\x07", serial);
+                return;
+            }
+        };
+        if matches == 1 {
+            self.insert(&first.as_bytes()[self.len..], writer, serial);
+            self.insert(b" ", writer, serial);
+        } else if common > self.len {
+            self.insert(&first.as_bytes()[self.len..common], writer, serial);
+        } else {
+            out(writer, "\r\n", serial);
+            let mut i = 0;
+            while let Some(c) = completions.candidate(i) {
+                i += 1;
+                if c.as_bytes().starts_with(&self.buf[..self.len]) {
+                    out(writer, c, serial);
+                    out(writer, "  ", serial);
+                }
+            }
+            out(writer, "\r\n", serial);
+            out(writer, self.prompt, serial);
+            out(writer, as_str(&self.buf[..self.len]), serial);
+        }
+    }
+    /// Handle a received byte. Returns `true` if the line is completed.
+    fn key<S: Sink + ?Sized, C: Completions + ?Sized>(&mut self, b: u8, completions: &C, writer: &mut Writer, serial: &mut S) -> bool {
+        if self.ready {
+            self.clear();
+        }
+        let last_cr = self.last_cr;
+        self.last_cr = b == b'\r';
+        match self.escape {
+            Escape::None => {}
+            Escape::Esc => {
+                self.escape = match b {
+                    b'[' => Escape::Csi(0),
+                    b'O' => Escape::Ss3,
+                    _ => Escape::None,
+                };
+                return false;
+            }
+            Escape::Csi(param) => {
+                match b {
+                    b'0'..=b'9' => {
+                        self.escape = Escape::Csi(param.saturating_mul(10).saturating_add(b - b'0'));
+                        return false;
+                    }
+                    0x20..=0x3F => { return false; }
+                    _ => {}
+                }
+                self.escape = Escape::None;
+                match (b, param) {
+                    (b'A', _) => self.up(writer, serial),
+                    (b'B', _) => self.down(writer, serial),
+                    (b'C', _) => self.right(writer, serial),
+                    (b'D', _) => self.left(writer, serial),
+                    (b'H', _) | (b'~', 1) | (b'~', 7) => self.home(writer, serial),
+                    (b'F', _) | (b'~', 4) | (b'~', 8) => self.end(writer, serial),
+                    (b'~', 3) => self.delete(writer, serial),
+                    _ => {}
+                }
+                return false;
+            }
+            Escape::Ss3 => {
+                self.escape = Escape::None;
+                match b {
+                    b'A' => self.up(writer, serial),
+                    b'B' => self.down(writer, serial),
+                    b'C' => self.right(writer, serial),
+                    b'D' => self.left(writer, serial),
+                    b'H' => self.home(writer, serial),
+                    b'F' => self.end(writer, serial),
+                    _ => {}
+                }
+                return false;
+            }
+        }
+        if self.utf8_len > 0 {
+            if b & 0xC0 == 0x80 {
+                self.utf8[self.utf8_len] = b;
+                self.utf8_len += 1;
+                if self.utf8_len == utf8_width(self.utf8[0]) {
+                    let c = self.utf8;
+                    self.insert(&c[..self.utf8_len], writer, serial);
+                    self.utf8_len = 0;
+                }
+                return false;
+            }
+            // An incomplete character is dropped.
+            self.utf8_len = 0;
+        }
+        match b {
+            b'\n' if last_cr => {}
+            b'\r' | b'\n' => {
+                out(writer, "\r\n", serial);
+                self.push_history();
+                self.browsing = None;
+                self.ready = true;
+                return true;
+            }
+            0x1B => { self.escape = Escape::Esc; }
+            0x01 => self.home(writer, serial),
+            0x05 => self.end(writer, serial),
+            0x04 => self.delete(writer, serial),
+            0x08 | 0x7F => self.backspace(writer, serial),
+            b'\t' => self.complete(completions, writer, serial),
+            0x15 => {
+                self.home(writer, serial);
+                out(writer, "\x1b[K", serial);
+                self.len = 0;
+            }
+            0x03 => {
+                out(writer, "^C\r\n", serial);
+                out(writer, self.prompt, serial);
+                self.clear();
+            }
+            0x20..=0x7E => self.insert(&[b], writer, serial),
+            0xC0..=0xF7 => {
+                self.utf8[0] = b;
+                self.utf8_len = 1;
+            }
+            _ => {}
+        }
+        false
+    }
+    /// Read the available bytes from `serial` until a line is completed, echoing and editing it on the terminal.
+    ///
+    /// Returns the line without its line ending, or `None` if no complete line is available yet.
+    /// The line is kept until the next call. Input which does not fit in `N` bytes is refused with a bell.
+    pub fn read_line<S: Source + Sink + ?Sized, C: Completions + ?Sized>(
+        &mut self,
+        writer: &mut Writer,
+        serial: &mut S,
+        completions: &C,
+    ) -> Result<Option<&str>, ReadError> {
+        loop {
+            let mut b = [0u8; 1];
+            match serial.read(&mut b) {
+                Ok(0) | Err(UsbError::WouldBlock) => { return Ok(None); }
+                Ok(_) => {}
+                Err(e) => { return Err(e.into()); }
+            }
+            if self.key(b[0], completions, writer, serial) {
+                return match core::str::from_utf8(&self.buf[..self.len]) {
+                    Ok(line) => Ok(Some(line)),
+                    Err(_) => Err(ReadError::Utf8),
+                };
+            }
+        }
+    }
+}
+
+impl<const N: usize, const H: usize> Default for LineEditor<'_, N, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
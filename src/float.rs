@@ -0,0 +1,325 @@
+//! Exact decimal expansion of floating-point numbers.
+//!
+//! A finite float is `m * 2^e`, which is the decimal `m * 2^e` if `e >= 0`, and `m * 5^-e * 10^e` otherwise.
+//! Both are computed exactly in base 10^9 limbs, so the digits are rounded once, half to even,
+//! and agree with `{:.N}` and `{:.Ne}` of `core::fmt`.
+
+use usb_device::class_prelude::UsbError;
+
+use crate::sink::write_all;
+use crate::{Sink, Writer};
+
+const BASE: u64 = 1_000_000_000;
+const POW10: [u32; 9] = [1, 10, 100, 1_000, 10_000, 100_000, 1_000_000, 10_000_000, 100_000_000];
+
+/// Limbs needed for `f32`: `(2^24 - 1) * 5^149` has 113 digits, and rounding may carry to one more.
+pub(crate) const F32_LIMBS: usize = 13;
+/// Limbs needed for `f64`: `(2^53 - 1) * 5^1074` has 767 digits, and rounding may carry to one more.
+pub(crate) const F64_LIMBS: usize = 86;
+
+/// Floating-point types which can be split into a sign, a mantissa and a binary exponent.
+pub(crate) trait Float: Copy {
+    fn is_nan(self) -> bool;
+    fn is_infinite(self) -> bool;
+    fn is_sign_negative(self) -> bool;
+    /// `(m, e)` with `|self| == m * 2^e`. Only for finite values.
+    fn decode(self) -> (u64, i32);
+}
+
+impl Float for f32 {
+    fn is_nan(self) -> bool {
+        f32::is_nan(self)
+    }
+    fn is_infinite(self) -> bool {
+        f32::is_infinite(self)
+    }
+    fn is_sign_negative(self) -> bool {
+        f32::is_sign_negative(self)
+    }
+    fn decode(self) -> (u64, i32) {
+        let bits = self.to_bits();
+        let exp = ((bits >> 23) & 0xFF) as i32;
+        let frac = (bits & 0x7F_FFFF) as u64;
+        match exp {
+            0 => (frac, -149),
+            _ => (frac | 1 << 23, exp - 150),
+        }
+    }
+}
+
+impl Float for f64 {
+    fn is_nan(self) -> bool {
+        f64::is_nan(self)
+    }
+    fn is_infinite(self) -> bool {
+        f64::is_infinite(self)
+    }
+    fn is_sign_negative(self) -> bool {
+        f64::is_sign_negative(self)
+    }
+    fn decode(self) -> (u64, i32) {
+        let bits = self.to_bits();
+        let exp = ((bits >> 52) & 0x7FF) as i32;
+        let frac = bits & 0xF_FFFF_FFFF_FFFF;
+        match exp {
+            0 => (frac, -1074),
+            _ => (frac | 1 << 52, exp - 1075),
+        }
+    }
+}
+
+/// Decimal `limbs * 10^-scale`, with `L` limbs of 9 digits, least significant first.
+struct Decimal<const L: usize> {
+    limbs: [u32; L],
+    len: usize,
+    scale: usize,
+}
+
+impl<const L: usize> Decimal<L> {
+    fn new(mantissa: u64, exp: i32) -> Self {
+        let mut dec = Self { limbs: [0; L], len: 0, scale: 0 };
+        if mantissa == 0 {
+            return dec;
+        }
+        // Fewer factors of 5 for the same value.
+        let shift = (mantissa.trailing_zeros() as i32).min(if exp < 0 { -exp } else { 0 });
+        let (m, mut e) = (mantissa >> shift, exp + shift);
+        dec.limbs[0] = (m % BASE) as u32;
+        dec.limbs[1] = (m / BASE % BASE) as u32;
+        dec.limbs[2] = (m / BASE / BASE) as u32;
+        dec.len = 3;
+        dec.trim();
+        if e >= 0 {
+            while e > 0 {
+                let n = e.min(29);
+                dec.mul(1 << n);
+                e -= n;
+            }
+        } else {
+            dec.scale = -e as usize;
+            while e < 0 {
+                let n = (-e).min(13);
+                dec.mul(5u32.pow(n as u32));
+                e += n;
+            }
+        }
+        dec
+    }
+    fn trim(&mut self) {
+        while self.len > 0 && self.limbs[self.len - 1] == 0 {
+            self.len -= 1;
+        }
+    }
+    /// Multiply by `f`, which is at most 5^13.
+    fn mul(&mut self, f: u32) {
+        let mut carry = 0u64;
+        for limb in self.limbs[..self.len].iter_mut() {
+            let x = *limb as u64 * f as u64 + carry;
+            *limb = (x % BASE) as u32;
+            carry = x / BASE;
+        }
+        while carry > 0 {
+            self.limbs[self.len] = (carry % BASE) as u32;
+            self.len += 1;
+            carry /= BASE;
+        }
+    }
+    /// Number of digits, or 0 if the value is 0.
+    fn digits(&self) -> usize {
+        match self.len {
+            0 => 0,
+            len => 9 * (len - 1) + POW10.iter().take_while(|&&p| p <= self.limbs[len - 1]).count(),
+        }
+    }
+    /// Digit multiplied by 10^`i`.
+    fn digit(&self, i: usize) -> u8 {
+        match self.limbs[..self.len].get(i / 9) {
+            Some(&limb) => (limb / POW10[i % 9] % 10) as u8,
+            None => 0,
+        }
+    }
+    /// Round off the digits below 10^`p`, half to even.
+    // `u32::is_multiple_of` needs Rust 1.87.
+    #[allow(clippy::manual_is_multiple_of)]
+    fn round(&mut self, p: usize) {
+        if p == 0 {
+            return;
+        }
+        if p > self.digits() {
+            self.len = 0;
+            return;
+        }
+        let first = self.digit(p - 1);
+        let (j, k) = ((p - 1) / 9, (p - 1) % 9);
+        let sticky = self.limbs[..j].iter().any(|&limb| limb != 0) || self.limbs[j] % POW10[k] != 0;
+        let up = first > 5 || (first == 5 && (sticky || self.digit(p) % 2 == 1));
+        let (j, k) = (p / 9, p % 9);
+        for limb in self.limbs[..j].iter_mut() {
+            *limb = 0;
+        }
+        if j < self.len {
+            self.limbs[j] -= self.limbs[j] % POW10[k];
+        }
+        if up {
+            let mut i = j;
+            let mut carry = POW10[k] as u64;
+            while carry > 0 {
+                let x = self.limbs[i] as u64 + carry;
+                self.limbs[i] = (x % BASE) as u32;
+                carry = x / BASE;
+                i += 1;
+            }
+            self.len = self.len.max(i);
+        }
+        self.trim();
+    }
+}
+
+/// Bytes staged to be written in chunks rather than one by one.
+struct Staged<'a, S: Sink + ?Sized> {
+    serial: &'a mut S,
+    buf: [u8; 32],
+    len: usize,
+    count: usize,
+}
+
+impl<'a, S: Sink + ?Sized> Staged<'a, S> {
+    fn new(serial: &'a mut S) -> Self {
+        Self { serial, buf: [0u8; 32], len: 0, count: 0 }
+    }
+    fn push(&mut self, b: u8) -> Result<(), (UsbError, usize)> {
+        if self.len == self.buf.len() {
+            self.flush()?;
+        }
+        self.buf[self.len] = b;
+        self.len += 1;
+        Ok(())
+    }
+    fn push_str(&mut self, s: &str) -> Result<(), (UsbError, usize)> {
+        s.bytes().try_for_each(|b| self.push(b))
+    }
+    fn flush(&mut self) -> Result<usize, (UsbError, usize)> {
+        if self.len > 0 {
+            match write_all(self.serial, &self.buf[..self.len]) {
+                Ok(n) => { self.count += n; }
+                Err((e, n)) => { return Err((e, self.count + n)); }
+            }
+            self.len = 0;
+        }
+        Ok(self.count)
+    }
+}
+
+/// Output "inf" or "NaN" after the sign, and return `true`, if `val` is not finite.
+fn non_finite<F: Float, S: Sink + ?Sized>(val: F, out: &mut Staged<S>) -> Result<bool, (UsbError, usize)> {
+    if val.is_nan() {
+        out.push_str("NaN")?;
+    } else if val.is_infinite() {
+        out.push_str("inf")?;
+    } else {
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+impl Writer {
+    /// Output `val` to `nodp` decimal places, like `{:.nodp}`.
+    pub(crate) fn _write_fixed<F: Float, S: Sink + ?Sized, const L: usize>(&mut self, val: F, nodp: usize, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        handle_err2!(self._prefix(serial), count);
+        let mut out = Staged::new(serial);
+        handle_err2!(fixed::<F, S, L>(val, nodp, &mut out).and_then(|_| out.flush()), count);
+        Ok(count)
+    }
+    /// Output `val` with `nodp` decimal places in exponential format, e.g. " 1.50e-03" for `1.5e-3` and `2`.
+    ///
+    /// Unlike `{:.nodp e}`, positive values start with a space, and the exponent is padded to 3 characters.
+    pub(crate) fn _write_exp<F: Float, S: Sink + ?Sized, const L: usize>(&mut self, val: F, nodp: usize, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        handle_err2!(self._prefix(serial), count);
+        let mut out = Staged::new(serial);
+        handle_err2!(exp::<F, S, L>(val, nodp, &mut out).and_then(|_| out.flush()), count);
+        Ok(count)
+    }
+}
+
+fn fixed<F: Float, S: Sink + ?Sized, const L: usize>(val: F, nodp: usize, out: &mut Staged<S>) -> Result<(), (UsbError, usize)> {
+    if val.is_sign_negative() && !val.is_nan() {
+        out.push(b'-')?;
+    }
+    if non_finite(val, out)? {
+        return Ok(());
+    }
+    let (m, e) = val.decode();
+    let mut dec = Decimal::<L>::new(m, e);
+    let scale = dec.scale;
+    let low = scale.saturating_sub(nodp);
+    dec.round(low);
+    // The units digit is 10^scale.
+    let high = dec.digits().max(scale + 1) - 1;
+    for i in (low..=high).rev() {
+        if i + 1 == scale {
+            out.push(b'.')?;
+        }
+        out.push(b'0' + dec.digit(i))?;
+    }
+    if nodp > scale {
+        if scale == 0 {
+            out.push(b'.')?;
+        }
+        for _ in scale..nodp {
+            out.push(b'0')?;
+        }
+    }
+    Ok(())
+}
+
+fn exp<F: Float, S: Sink + ?Sized, const L: usize>(val: F, nodp: usize, out: &mut Staged<S>) -> Result<(), (UsbError, usize)> {
+    out.push(if val.is_sign_negative() && !val.is_nan() { b'-' } else { b' ' })?;
+    if non_finite(val, out)? {
+        return Ok(());
+    }
+    let (m, e) = val.decode();
+    let mut dec = Decimal::<L>::new(m, e);
+    let (top, exp) = match dec.digits() {
+        0 => (0, 0),
+        digits => {
+            dec.round((digits - 1).saturating_sub(nodp));
+            // Rounding may carry to one more digit, e.g. 9.99 to 10.0.
+            let top = dec.digits() - 1;
+            (top, top as i32 - dec.scale as i32)
+        }
+    };
+    out.push(b'0' + dec.digit(top))?;
+    if nodp > 0 {
+        out.push(b'.')?;
+    }
+    for i in (top.saturating_sub(nodp)..top).rev() {
+        out.push(b'0' + dec.digit(i))?;
+    }
+    for _ in top..nodp {
+        out.push(b'0')?;
+    }
+    out.push(b'e')?;
+    let abs = exp.unsigned_abs();
+    if exp < 0 {
+        out.push(b'-')?;
+        if abs < 10 {
+            out.push(b'0')?;
+        }
+    } else {
+        if abs < 100 {
+            out.push(b'0')?;
+        }
+        if abs < 10 {
+            out.push(b'0')?;
+        }
+    }
+    if abs >= 100 {
+        out.push(b'0' + (abs / 100) as u8)?;
+    }
+    if abs >= 10 {
+        out.push(b'0' + (abs / 10 % 10) as u8)?;
+    }
+    out.push(b'0' + (abs % 10) as u8)
+}
@@ -0,0 +1,124 @@
+//! Formatting of numbers into a byte buffer, without a serial port.
+//!
+//! Each function returns the text written into `buf`, e.g. to show it on an LCD or to put it into
+//! a log file or a radio packet. The text is cut off at the end of `buf`, so it should be as long as
+//! the maximum length of the output (see the README), e.g. 11 bytes for `i32`.
+//!
+//! ```ignore
+//! let mut buf = [0u8; 16];
+//! lcd.write_str(fmt_f32(temp, 1, &mut buf));
+//! ```
+
+use crate::{SliceSink, Writer};
+
+macro_rules! fmt_int {
+    ($(#[$meta: meta])*
+    $type: ty, $name: ident, $f: ident) => {
+        $(#[$meta])*
+        pub fn $name(val: $type, buf: &mut [u8]) -> &str {
+            let mut sink = SliceSink::new(buf);
+            Writer::new().$f(val, &mut sink).ok();
+            sink.into_str()
+        }
+    };
+}
+macro_rules! fmt_float {
+    ($(#[$meta: meta])*
+    $type: ty, $name: ident, $f: ident) => {
+        $(#[$meta])*
+        pub fn $name(val: $type, nodp: usize, buf: &mut [u8]) -> &str {
+            let mut sink = SliceSink::new(buf);
+            Writer::new().$f(val, nodp, &mut sink).ok();
+            sink.into_str()
+        }
+    };
+}
+macro_rules! fmt_int_slice {
+    ($(#[$meta: meta])*
+    $type: ty, $name: ident, $f: ident) => {
+        $(#[$meta])*
+        pub fn $name<'b>(val: &[$type], buf: &'b mut [u8]) -> &'b str {
+            let mut sink = SliceSink::new(buf);
+            Writer::new().$f(val, &mut sink).ok();
+            sink.into_str()
+        }
+    };
+}
+macro_rules! fmt_float_slice {
+    ($(#[$meta: meta])*
+    $type: ty, $name: ident, $f: ident) => {
+        $(#[$meta])*
+        pub fn $name<'b>(val: &[$type], nodp: usize, buf: &'b mut [u8]) -> &'b str {
+            let mut sink = SliceSink::new(buf);
+            Writer::new().$f(val, nodp, &mut sink).ok();
+            sink.into_str()
+        }
+    };
+}
+
+fmt_int!(/// Format `i8`.
+    i8, fmt_i8, write_i8);
+fmt_int!(/// Format `i16`.
+    i16, fmt_i16, write_i16);
+fmt_int!(/// Format `i32`.
+    i32, fmt_i32, write_i32);
+fmt_int!(/// Format `i64`.
+    i64, fmt_i64, write_i64);
+fmt_int!(/// Format `isize`.
+    isize, fmt_isize, write_isize);
+fmt_int!(/// Format `u8`.
+    u8, fmt_u8, write_u8);
+fmt_int!(/// Format `u16`.
+    u16, fmt_u16, write_u16);
+fmt_int!(/// Format `u32`.
+    u32, fmt_u32, write_u32);
+fmt_int!(/// Format `u64`.
+    u64, fmt_u64, write_u64);
+fmt_int!(/// Format `usize`.
+    usize, fmt_usize, write_usize);
+fmt_int!(/// Format `u8` in hexadecimal, zero-padded to 2 digits (e.g. "0x0A").
+    u8, fmt_u8_hex, write_u8_hex);
+fmt_int!(/// Format `u16` in hexadecimal, zero-padded to 4 digits (e.g. "0x00FF").
+    u16, fmt_u16_hex, write_u16_hex);
+fmt_int!(/// Format `u32` in hexadecimal, zero-padded to 8 digits (e.g. "0x20001FF0").
+    u32, fmt_u32_hex, write_u32_hex);
+fmt_int!(/// Format `u64` in hexadecimal, zero-padded to 16 digits.
+    u64, fmt_u64_hex, write_u64_hex);
+fmt_int!(/// Format `usize` in hexadecimal, zero-padded to the width of `usize`.
+    usize, fmt_usize_hex, write_usize_hex);
+fmt_float!(/// Format `f32` to `nodp` decimal places.
+    f32, fmt_f32, write_f32);
+fmt_float!(/// Format `f64` to `nodp` decimal places.
+    f64, fmt_f64, write_f64);
+fmt_float!(/// Format `f32` to `nodp` decimal places in exponential format (e.g. " 1.50e-03").
+    f32, fmt_f32_exp, write_f32_exp);
+fmt_float!(/// Format `f64` to `nodp` decimal places in exponential format (e.g. " 1.50e-03").
+    f64, fmt_f64_exp, write_f64_exp);
+fmt_int_slice!(/// Format `&[i8]` (e.g. "[ 1, -2, ]").
+    i8, fmt_i8_slice, write_i8_slice);
+fmt_int_slice!(/// Format `&[i16]` (e.g. "[ 1, -2, ]").
+    i16, fmt_i16_slice, write_i16_slice);
+fmt_int_slice!(/// Format `&[i32]` (e.g. "[ 1, -2, ]").
+    i32, fmt_i32_slice, write_i32_slice);
+fmt_int_slice!(/// Format `&[i64]` (e.g. "[ 1, -2, ]").
+    i64, fmt_i64_slice, write_i64_slice);
+fmt_int_slice!(/// Format `&[isize]` (e.g. "[ 1, -2, ]").
+    isize, fmt_isize_slice, write_isize_slice);
+fmt_int_slice!(/// Format `&[u8]` (e.g. "[ 1, 2, ]").
+    u8, fmt_u8_slice, write_u8_slice);
+fmt_int_slice!(/// Format `&[u16]` (e.g. "[ 1, 2, ]").
+    u16, fmt_u16_slice, write_u16_slice);
+fmt_int_slice!(/// Format `&[u32]` (e.g. "[ 1, 2, ]").
+    u32, fmt_u32_slice, write_u32_slice);
+fmt_int_slice!(/// Format `&[u64]` (e.g. "[ 1, 2, ]").
+    u64, fmt_u64_slice, write_u64_slice);
+fmt_int_slice!(/// Format `&[usize]` (e.g. "[ 1, 2, ]").
+    usize, fmt_usize_slice, write_usize_slice);
+fmt_float_slice!(/// Format `&[f32]`, each element to `nodp` decimal places.
+    f32, fmt_f32_slice, write_f32_slice);
+fmt_float_slice!(/// Format `&[f64]`, each element to `nodp` decimal places.
+    f64, fmt_f64_slice, write_f64_slice);
+fmt_float_slice!(/// Format `&[f32]`, each element to `nodp` decimal places in exponential format.
+    f32, fmt_f32_slice_exp, write_f32_slice_exp);
+fmt_float_slice!(/// Format `&[f64]`, each element to `nodp` decimal places in exponential format.
+    f64, fmt_f64_slice_exp, write_f64_slice_exp);
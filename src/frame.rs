@@ -0,0 +1,331 @@
+//! Binary frames for high-rate telemetry.
+//!
+//! A frame is a message ID, a payload of little-endian values and a CRC over both,
+//! COBS encoded and terminated by 0x00. `FrameWriter` encodes while writing, with a buffer of one COBS block
+//! (254 bytes) instead of the whole frame. `FrameDecoder` decodes the stream, e.g. on the host.
+//!
+//! ```ignore
+//! let mut frame = FrameWriter::new(Checksum::Crc16);
+//! frame.begin(0x01, &mut serial)?;
+//! frame.write_u32(timestamp, &mut serial)?;
+//! frame.write_f32_slice(&[ax, ay, az, gx, gy, gz], &mut serial)?;
+//! frame.end(&mut serial)?;
+//!
+//! // On the receiving side
+//! let mut decoder = FrameDecoder::<64>::new(Checksum::Crc16);
+//! for &b in received {
+//!     if let Some(Ok(frame)) = decoder.feed(b) {
+//!         let mut payload = frame.reader();
+//!         let timestamp = payload.read_u32();
+//!     }
+//! }
+//! ```
+
+use usb_device::class_prelude::UsbError;
+
+use crate::crc::{Crc16, Crc32};
+use crate::sink::write_all;
+use crate::Sink;
+
+/// Maximum number of non-zero bytes in a COBS block.
+const BLOCK: usize = 254;
+
+/// Checksum appended to frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Checksum {
+    /// `Crc16`, appended as 2 little-endian bytes.
+    Crc16,
+    /// `Crc32`, appended as 4 little-endian bytes.
+    Crc32,
+}
+
+impl Checksum {
+    /// Number of bytes of the checksum.
+    pub const fn size(self) -> usize {
+        match self {
+            Checksum::Crc16 => 2,
+            Checksum::Crc32 => 4,
+        }
+    }
+}
+
+macro_rules! write_le {
+    ($(#[$meta: meta])*
+    $type: ty, $name: ident) => {
+        $(#[$meta])*
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: $type, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+            self.write_bytes(&val.to_le_bytes(), serial)
+        }
+    };
+}
+macro_rules! write_le_slice {
+    ($(#[$meta: meta])*
+    $type: ty, $name: ident) => {
+        $(#[$meta])*
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: &[$type], serial: &mut S) -> Result<usize, (UsbError, usize)> {
+            let mut count = 0;
+            for &val in val.iter() {
+                handle_err2!(self.write_bytes(&val.to_le_bytes(), serial), count);
+            }
+            Ok(count)
+        }
+    };
+}
+
+/// Streaming encoder of frames.
+pub struct FrameWriter {
+    block: [u8; BLOCK],
+    len: usize,
+    checksum: Checksum,
+    crc16: Crc16,
+    crc32: Crc32,
+}
+
+impl FrameWriter {
+    /// Initialize FrameWriter.
+    pub const fn new(checksum: Checksum) -> Self {
+        Self { block: [0u8; BLOCK], len: 0, checksum, crc16: Crc16::new(), crc32: Crc32::new() }
+    }
+    /// Output the pending COBS block with its code byte.
+    fn flush_block<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        handle_err2!(write_all(serial, &[self.len as u8 + 1]), count);
+        let len = self.len;
+        self.len = 0;
+        handle_err2!(write_all(serial, &self.block[..len]), count);
+        Ok(count)
+    }
+    /// COBS encode `data` without adding it to the checksum.
+    fn encode<S: Sink + ?Sized>(&mut self, data: &[u8], serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        for &b in data.iter() {
+            if b == 0 {
+                handle_err2!(self.flush_block(serial), count);
+                continue;
+            }
+            self.block[self.len] = b;
+            self.len += 1;
+            if self.len == BLOCK {
+                handle_err2!(self.flush_block(serial), count);
+            }
+        }
+        Ok(count)
+    }
+    /// Start a frame with message ID `id`.
+    /// Any unfinished frame is discarded.
+    pub fn begin<S: Sink + ?Sized>(&mut self, id: u8, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        self.len = 0;
+        self.crc16 = Crc16::new();
+        self.crc32 = Crc32::new();
+        self.write_bytes(&[id], serial)
+    }
+    /// Add raw bytes to the payload.
+    pub fn write_bytes<S: Sink + ?Sized>(&mut self, data: &[u8], serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        match self.checksum {
+            Checksum::Crc16 => self.crc16.update(data),
+            Checksum::Crc32 => self.crc32.update(data),
+        }
+        self.encode(data, serial)
+    }
+    write_le!(/// Add `u8` to the payload.
+        u8, write_u8);
+    write_le!(/// Add `i8` to the payload.
+        i8, write_i8);
+    write_le!(/// Add `u16` to the payload in little endian.
+        u16, write_u16);
+    write_le!(/// Add `i16` to the payload in little endian.
+        i16, write_i16);
+    write_le!(/// Add `u32` to the payload in little endian.
+        u32, write_u32);
+    write_le!(/// Add `i32` to the payload in little endian.
+        i32, write_i32);
+    write_le!(/// Add `u64` to the payload in little endian.
+        u64, write_u64);
+    write_le!(/// Add `i64` to the payload in little endian.
+        i64, write_i64);
+    write_le!(/// Add `f32` to the payload in little endian.
+        f32, write_f32);
+    write_le!(/// Add `f64` to the payload in little endian.
+        f64, write_f64);
+    write_le_slice!(/// Add the elements of `&[i16]` to the payload in little endian.
+        i16, write_i16_slice);
+    write_le_slice!(/// Add the elements of `&[u16]` to the payload in little endian.
+        u16, write_u16_slice);
+    write_le_slice!(/// Add the elements of `&[i32]` to the payload in little endian.
+        i32, write_i32_slice);
+    write_le_slice!(/// Add the elements of `&[u32]` to the payload in little endian.
+        u32, write_u32_slice);
+    write_le_slice!(/// Add the elements of `&[i64]` to the payload in little endian.
+        i64, write_i64_slice);
+    write_le_slice!(/// Add the elements of `&[u64]` to the payload in little endian.
+        u64, write_u64_slice);
+    write_le_slice!(/// Add the elements of `&[f32]` to the payload in little endian.
+        f32, write_f32_slice);
+    write_le_slice!(/// Add the elements of `&[f64]` to the payload in little endian.
+        f64, write_f64_slice);
+    /// Append the checksum and the 0x00 delimiter to finish the frame.
+    pub fn end<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        match self.checksum {
+            Checksum::Crc16 => {
+                let crc = self.crc16.finish().to_le_bytes();
+                handle_err2!(self.encode(&crc, serial), count);
+            }
+            Checksum::Crc32 => {
+                let crc = self.crc32.finish().to_le_bytes();
+                handle_err2!(self.encode(&crc, serial), count);
+            }
+        }
+        handle_err2!(self.flush_block(serial), count);
+        handle_err2!(write_all(serial, &[0]), count);
+        Ok(count)
+    }
+}
+
+/// Errors of `FrameDecoder`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameError {
+    /// The decoded frame is longer than the buffer of the decoder.
+    Overflow,
+    /// The COBS encoding is broken, e.g. the frame was cut off.
+    Encoding,
+    /// The frame is too short to contain a message ID and the checksum.
+    TooShort,
+    /// The checksum does not match.
+    Checksum,
+}
+
+/// A decoded frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Frame<'a> {
+    pub id: u8,
+    pub payload: &'a [u8],
+}
+
+impl<'a> Frame<'a> {
+    /// Reader of little-endian values from the payload.
+    pub fn reader(&self) -> PayloadReader<'a> {
+        PayloadReader { data: self.payload }
+    }
+}
+
+macro_rules! read_le {
+    ($(#[$meta: meta])*
+    $type: ty, $name: ident) => {
+        $(#[$meta])*
+        pub fn $name(&mut self) -> Option<$type> {
+            const N: usize = core::mem::size_of::<$type>();
+            if self.data.len() < N {
+                return None;
+            }
+            let mut bytes = [0u8; N];
+            bytes.copy_from_slice(&self.data[..N]);
+            self.data = &self.data[N..];
+            Some(<$type>::from_le_bytes(bytes))
+        }
+    };
+}
+
+/// Reads the values added by `FrameWriter` in order.
+#[derive(Clone, Copy, Debug)]
+pub struct PayloadReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> PayloadReader<'a> {
+    /// Bytes not read yet.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+    read_le!(/// Read `u8`, or `None` at the end of the payload.
+        u8, read_u8);
+    read_le!(/// Read `i8`, or `None` at the end of the payload.
+        i8, read_i8);
+    read_le!(/// Read `u16`, or `None` at the end of the payload.
+        u16, read_u16);
+    read_le!(/// Read `i16`, or `None` at the end of the payload.
+        i16, read_i16);
+    read_le!(/// Read `u32`, or `None` at the end of the payload.
+        u32, read_u32);
+    read_le!(/// Read `i32`, or `None` at the end of the payload.
+        i32, read_i32);
+    read_le!(/// Read `u64`, or `None` at the end of the payload.
+        u64, read_u64);
+    read_le!(/// Read `i64`, or `None` at the end of the payload.
+        i64, read_i64);
+    read_le!(/// Read `f32`, or `None` at the end of the payload.
+        f32, read_f32);
+    read_le!(/// Read `f64`, or `None` at the end of the payload.
+        f64, read_f64);
+}
+
+/// Decoder of frames of up to `N` bytes (message ID, payload and checksum).
+pub struct FrameDecoder<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+    /// Code byte of the current COBS block.
+    code: u8,
+    /// Bytes left in the current COBS block.
+    remaining: u8,
+    overflow: bool,
+    checksum: Checksum,
+}
+
+impl<const N: usize> FrameDecoder<N> {
+    /// Initialize FrameDecoder.
+    pub const fn new(checksum: Checksum) -> Self {
+        Self { buf: [0u8; N], len: 0, code: 0, remaining: 0, overflow: false, checksum }
+    }
+    fn push(&mut self, b: u8) {
+        if self.len < N {
+            self.buf[self.len] = b;
+            self.len += 1;
+        } else {
+            self.overflow = true;
+        }
+    }
+    /// Feed a received byte.
+    /// Returns the frame or its error when `byte` is the delimiter, and `None` otherwise.
+    pub fn feed(&mut self, byte: u8) -> Option<Result<Frame<'_>, FrameError>> {
+        if byte != 0 {
+            if self.remaining == 0 {
+                // A code byte; the previous block ended with a zero unless it was full.
+                if self.code != 0 && self.code != 0xFF {
+                    self.push(0);
+                }
+                self.code = byte;
+                self.remaining = byte - 1;
+            } else {
+                self.push(byte);
+                self.remaining -= 1;
+            }
+            return None;
+        }
+        let len = self.len;
+        let broken = self.remaining != 0;
+        let overflow = self.overflow;
+        self.len = 0;
+        self.code = 0;
+        self.remaining = 0;
+        self.overflow = false;
+        if overflow {
+            return Some(Err(FrameError::Overflow));
+        }
+        if broken {
+            return Some(Err(FrameError::Encoding));
+        }
+        let crc_len = self.checksum.size();
+        if len < 1 + crc_len {
+            return Some(Err(FrameError::TooShort));
+        }
+        let (data, crc) = self.buf[..len].split_at(len - crc_len);
+        let valid = match self.checksum {
+            Checksum::Crc16 => Crc16::checksum(data).to_le_bytes() == crc,
+            Checksum::Crc32 => Crc32::checksum(data).to_le_bytes() == crc,
+        };
+        if !valid {
+            return Some(Err(FrameError::Checksum));
+        }
+        Some(Ok(Frame { id: data[0], payload: &data[1..] }))
+    }
+}
@@ -0,0 +1,233 @@
+//! Byte-stuffing framers: SLIP (RFC 1055) and HDLC-like asynchronous framing (RFC 1662).
+//!
+//! `SlipSink` and `HdlcSink` wrap another sink, so anything output by `Writer` (or `FrameWriter`, etc.)
+//! can be sent inside frames. Each frame is started by the first write and finished by `end_frame`.
+//!
+//! ```ignore
+//! let mut writer = Writer::new();
+//! let mut hdlc = HdlcSink::new(&mut serial);
+//! writer.write_str("temp=", &mut hdlc)?;
+//! writer.write_f32(23.456, 2, &mut hdlc)?;
+//! // 0x7E "temp=23.46" FCS-16 0x7E
+//! hdlc.end_frame()?;
+//! ```
+
+use usb_device::class_prelude::UsbError;
+
+use crate::crc::Crc16;
+use crate::sink::write_all;
+use crate::Sink;
+
+const SLIP_END: u8 = 0xC0;
+const SLIP_ESC: u8 = 0xDB;
+const SLIP_ESC_END: u8 = 0xDC;
+const SLIP_ESC_ESC: u8 = 0xDD;
+
+const HDLC_FLAG: u8 = 0x7E;
+const HDLC_ESC: u8 = 0x7D;
+
+/// Write `data` to `inner`, replacing the bytes for which `escape` returns `Some` by their escape sequences.
+///
+/// Returns the number of bytes of `data` consumed. If only the first byte of an escape sequence could be written,
+/// the second one is kept in `pending` and written first on the next call.
+fn stuff<S: Sink + ?Sized>(
+    inner: &mut S,
+    pending: &mut Option<u8>,
+    data: &[u8],
+    escape: impl Fn(u8) -> Option<[u8; 2]>,
+) -> Result<usize, UsbError> {
+    if let Some(b) = *pending {
+        match inner.write(&[b]) {
+            Ok(0) => { return Err(UsbError::WouldBlock); }
+            Ok(_) => { *pending = None; }
+            Err(e) => { return Err(e); }
+        }
+    }
+    let mut count = 0;
+    while count < data.len() {
+        let rest = &data[count..];
+        let run = rest.iter().position(|&b| escape(b).is_some()).unwrap_or(rest.len());
+        let res = if run > 0 {
+            inner.write(&rest[..run]).map(|n| (n, n == run))
+        } else {
+            let seq = escape(rest[0]).unwrap();
+            match inner.write(&seq) {
+                Ok(0) => Ok((0, false)),
+                Ok(1) => {
+                    *pending = Some(seq[1]);
+                    Ok((1, false))
+                }
+                Ok(_) => Ok((1, true)),
+                Err(e) => Err(e),
+            }
+        };
+        match res {
+            Ok((n, complete)) => {
+                count += n;
+                if !complete {
+                    break;
+                }
+            }
+            Err(e) if count == 0 => { return Err(e); }
+            Err(_) => { break; }
+        }
+    }
+    if count == 0 {
+        return Err(UsbError::WouldBlock);
+    }
+    Ok(count)
+}
+
+/// Write the escape sequence of `b`, or `b` itself.
+fn write_escaped<S: Sink + ?Sized>(inner: &mut S, b: u8, escape: impl Fn(u8) -> Option<[u8; 2]>) -> Result<usize, (UsbError, usize)> {
+    match escape(b) {
+        Some(seq) => write_all(inner, &seq),
+        None => write_all(inner, &[b]),
+    }
+}
+
+fn slip_escape(b: u8) -> Option<[u8; 2]> {
+    match b {
+        SLIP_END => Some([SLIP_ESC, SLIP_ESC_END]),
+        SLIP_ESC => Some([SLIP_ESC, SLIP_ESC_ESC]),
+        _ => None,
+    }
+}
+
+/// Sink adapter sending the written bytes as SLIP frames.
+///
+/// Every frame is preceded and followed by END (0xC0), so that noise on the line is flushed
+/// by the receiver before the frame.
+pub struct SlipSink<S> {
+    inner: S,
+    pending: Option<u8>,
+    started: bool,
+}
+
+impl<S: Sink> SlipSink<S> {
+    /// Initialize SlipSink writing to `inner`.
+    pub const fn new(inner: S) -> Self {
+        Self { inner, pending: None, started: false }
+    }
+    /// The underlying sink.
+    pub fn inner(&mut self) -> &mut S {
+        &mut self.inner
+    }
+    /// Take the underlying sink back.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+    /// Finish the current frame.
+    ///
+    /// If the underlying sink fails, the frame is left incomplete and the next write starts a new one.
+    pub fn end_frame(&mut self) -> Result<usize, (UsbError, usize)> {
+        let pending = self.pending.take();
+        self.started = false;
+        let mut count = 0;
+        if let Some(b) = pending {
+            handle_err2!(write_all(&mut self.inner, &[b]), count);
+        }
+        handle_err2!(write_all(&mut self.inner, &[SLIP_END]), count);
+        Ok(count)
+    }
+}
+
+impl<S: Sink> Sink for SlipSink<S> {
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        if !self.started {
+            match self.inner.write(&[SLIP_END]) {
+                Ok(0) => { return Err(UsbError::WouldBlock); }
+                Ok(_) => { self.started = true; }
+                Err(e) => { return Err(e); }
+            }
+        }
+        stuff(&mut self.inner, &mut self.pending, data, slip_escape)
+    }
+}
+
+/// Sink adapter sending the written bytes as HDLC-like frames (RFC 1662 asynchronous framing).
+///
+/// Frames are delimited by the flag 0x7E, and end with the FCS-16 (`Crc16`) of their contents in little endian.
+/// 0x7E, 0x7D and the control characters selected by the ACCM are escaped as 0x7D followed by the byte XOR 0x20.
+/// No address or control field is added; write them as part of the contents if the receiver expects them.
+pub struct HdlcSink<S> {
+    inner: S,
+    pending: Option<u8>,
+    started: bool,
+    accm: u32,
+    fcs: Crc16,
+}
+
+impl<S: Sink> HdlcSink<S> {
+    /// Initialize HdlcSink writing to `inner`, escaping no control characters.
+    pub const fn new(inner: S) -> Self {
+        Self { inner, pending: None, started: false, accm: 0, fcs: Crc16::new() }
+    }
+    /// Set the Async-Control-Character-Map: bit `n` set escapes the control character `n` (0x00..=0x1F).
+    /// `0xFFFF_FFFF` escapes all of them, as PPP does by default.
+    pub const fn with_accm(mut self, accm: u32) -> Self {
+        self.accm = accm;
+        self
+    }
+    /// The underlying sink.
+    pub fn inner(&mut self) -> &mut S {
+        &mut self.inner
+    }
+    /// Take the underlying sink back.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+    fn escape(accm: u32) -> impl Fn(u8) -> Option<[u8; 2]> {
+        move |b| {
+            if b == HDLC_FLAG || b == HDLC_ESC || (b < 0x20 && accm & (1 << b) != 0) {
+                Some([HDLC_ESC, b ^ 0x20])
+            } else {
+                None
+            }
+        }
+    }
+    /// Append the FCS and the closing flag to finish the current frame.
+    ///
+    /// If the underlying sink fails, the frame is left incomplete and the next write starts a new one.
+    /// The receiver discards the incomplete frame because of its FCS.
+    pub fn end_frame(&mut self) -> Result<usize, (UsbError, usize)> {
+        let pending = self.pending.take();
+        let fcs = self.fcs.finish().to_le_bytes();
+        let started = self.started;
+        self.started = false;
+        self.fcs = Crc16::new();
+        let mut count = 0;
+        if let Some(b) = pending {
+            handle_err2!(write_all(&mut self.inner, &[b]), count);
+        }
+        if !started {
+            handle_err2!(write_all(&mut self.inner, &[HDLC_FLAG]), count);
+        }
+        for &b in fcs.iter() {
+            handle_err2!(write_escaped(&mut self.inner, b, Self::escape(self.accm)), count);
+        }
+        handle_err2!(write_all(&mut self.inner, &[HDLC_FLAG]), count);
+        Ok(count)
+    }
+}
+
+impl<S: Sink> Sink for HdlcSink<S> {
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        if !self.started {
+            match self.inner.write(&[HDLC_FLAG]) {
+                Ok(0) => { return Err(UsbError::WouldBlock); }
+                Ok(_) => { self.started = true; }
+                Err(e) => { return Err(e); }
+            }
+        }
+        let n = stuff(&mut self.inner, &mut self.pending, data, Self::escape(self.accm))?;
+        self.fcs.update(&data[..n]);
+        Ok(n)
+    }
+}
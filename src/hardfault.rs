@@ -0,0 +1,123 @@
+//! HardFault reports over serial (requires the `hardfault` feature).
+//!
+//! `write_hardfault` outputs the stacked exception frame, the fault status registers and the top of the stack
+//! in hexadecimal, without allocation or formatting machinery that could panic.
+//! With the `panic-serial` feature, `report` outputs to the sink registered by `panic::register`.
+//!
+//! ```ignore
+//! #[exception]
+//! unsafe fn HardFault(frame: &ExceptionFrame) -> ! {
+//!     serial_write::hardfault::report(frame)
+//! }
+//! ```
+
+use cortex_m_rt::ExceptionFrame;
+use usb_device::class_prelude::UsbError;
+
+use crate::{Sink, Writer};
+
+/// Number of stack words output by `report`.
+pub const STACK_WORDS: usize = 16;
+
+const CPUID: *const u32 = 0xE000_ED00 as *const u32;
+const CFSR: *const u32 = 0xE000_ED28 as *const u32;
+const HFSR: *const u32 = 0xE000_ED2C as *const u32;
+const MMFAR: *const u32 = 0xE000_ED34 as *const u32;
+const BFAR: *const u32 = 0xE000_ED38 as *const u32;
+
+extern "C" {
+    // Initial stack pointer, defined by the linker script of `cortex-m-rt`.
+    static _stack_start: u32;
+}
+
+fn write_reg<S: Sink + ?Sized>(writer: &mut Writer, name: &str, val: u32, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+    let mut count = 0;
+    handle_err2!(writer.write_str(name, serial), count);
+    handle_err2!(writer.write_u32_hex(val, serial), count);
+    handle_err1!(writer._writeln(serial), count);
+    Ok(count)
+}
+
+/// Output R0-R3, R12, LR, PC and xPSR stacked in `frame`.
+pub fn write_exception_frame<S: Sink + ?Sized>(writer: &mut Writer, frame: &ExceptionFrame, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+    let mut count = 0;
+    handle_err2!(write_reg(writer, "R0   = ", frame.r0(), serial), count);
+    handle_err2!(write_reg(writer, "R1   = ", frame.r1(), serial), count);
+    handle_err2!(write_reg(writer, "R2   = ", frame.r2(), serial), count);
+    handle_err2!(write_reg(writer, "R3   = ", frame.r3(), serial), count);
+    handle_err2!(write_reg(writer, "R12  = ", frame.r12(), serial), count);
+    handle_err2!(write_reg(writer, "LR   = ", frame.lr(), serial), count);
+    handle_err2!(write_reg(writer, "PC   = ", frame.pc(), serial), count);
+    handle_err2!(write_reg(writer, "xPSR = ", frame.xpsr(), serial), count);
+    Ok(count)
+}
+
+/// Output CFSR, HFSR, MMFAR and BFAR.
+///
+/// ARMv6-M cores (e.g. Cortex-M0+ of RP2040) do not have these registers,
+/// so only a note is output on them.
+pub fn write_fault_status<S: Sink + ?Sized>(writer: &mut Writer, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+    let mut count = 0;
+    // The architecture field of CPUID is 0xF for ARMv7-M and ARMv8-M Mainline.
+    let cpuid = unsafe { core::ptr::read_volatile(CPUID) };
+    if (cpuid >> 16) & 0xF != 0xF {
+        handle_err2!(writer.writeln_str("(no fault status registers)", serial), count);
+        return Ok(count);
+    }
+    unsafe {
+        handle_err2!(write_reg(writer, "CFSR  = ", core::ptr::read_volatile(CFSR), serial), count);
+        handle_err2!(write_reg(writer, "HFSR  = ", core::ptr::read_volatile(HFSR), serial), count);
+        handle_err2!(write_reg(writer, "MMFAR = ", core::ptr::read_volatile(MMFAR), serial), count);
+        handle_err2!(write_reg(writer, "BFAR  = ", core::ptr::read_volatile(BFAR), serial), count);
+    }
+    Ok(count)
+}
+
+/// Output up to `words` words of the stack above `frame`, 4 words per line.
+/// Words beyond the initial stack pointer are not read.
+pub fn write_stack<S: Sink + ?Sized>(writer: &mut Writer, frame: &ExceptionFrame, words: usize, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+    let mut count = 0;
+    // The stack of the faulting code continues after the 8 stacked words.
+    let start = (frame as *const ExceptionFrame as usize) + 32;
+    let end = core::ptr::addr_of!(_stack_start) as usize;
+    let words = if start >= end {
+        0
+    } else if (end - start) / 4 < words {
+        (end - start) / 4
+    } else {
+        words
+    };
+    handle_err2!(writer.write_str("Stack at ", serial), count);
+    handle_err2!(writer.write_usize_hex(start, serial), count);
+    handle_err2!(writer.writeln_str(":", serial), count);
+    for i in 0..words {
+        let val = unsafe { core::ptr::read_volatile((start + 4 * i) as *const u32) };
+        handle_err2!(writer.write_u32_hex(val, serial), count);
+        if i % 4 == 3 || i == words - 1 {
+            handle_err1!(writer._writeln(serial), count);
+        } else {
+            handle_err2!(writer.write_str(" ", serial), count);
+        }
+    }
+    Ok(count)
+}
+
+/// Output the exception frame, the fault status registers and `stack_words` words of the stack.
+pub fn write_hardfault<S: Sink + ?Sized>(writer: &mut Writer, frame: &ExceptionFrame, stack_words: usize, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+    let mut count = 0;
+    handle_err2!(writer.write_str("\r\n", serial), count);
+    handle_err2!(writer.writeln_str("HardFault:", serial), count);
+    handle_err2!(write_exception_frame(writer, frame, serial), count);
+    handle_err2!(write_fault_status(writer, serial), count);
+    handle_err2!(write_stack(writer, frame, stack_words, serial), count);
+    Ok(count)
+}
+
+/// Output the HardFault report to the sink registered by `panic::register`,
+/// then halt or reset as registered.
+#[cfg(feature = "panic-serial")]
+pub fn report(frame: &ExceptionFrame) -> ! {
+    crate::panic::report_fatal(|serial| {
+        let _ = write_hardfault(&mut Writer::new(), frame, STACK_WORDS, serial);
+    })
+}
@@ -0,0 +1,191 @@
+//! Line-oriented input from a `Source`.
+//!
+//! ```ignore
+//! let mut writer = Writer::new();
+//! let mut reader = LineReader::<64>::new();
+//! loop {
+//!     if !usb_dev.poll(&mut [&mut serial]) {
+//!         continue;
+//!     }
+//!     match reader.read_line_echo(&mut writer, &mut serial) {
+//!         Ok(Some("led on")) => led.set_high().unwrap(),
+//!         Ok(Some(line)) => { /* ... */ }
+//!         Ok(None) => {}
+//!         Err(ReadError::Overflow) => { writer.write_str("line too long\r\n", &mut serial).ok(); }
+//!         Err(_) => {}
+//!     }
+//! }
+//! ```
+
+use usb_device::class_prelude::UsbError;
+
+use crate::{Sink, Source, Writer};
+
+/// Errors of `LineReader`.
+#[derive(Debug)]
+pub enum ReadError {
+    /// Error of the underlying source.
+    Usb(UsbError),
+    /// The line was longer than the buffer. It was discarded.
+    Overflow,
+    /// The line is not valid UTF-8. It was discarded.
+    Utf8,
+}
+
+impl From<UsbError> for ReadError {
+    fn from(e: UsbError) -> Self {
+        ReadError::Usb(e)
+    }
+}
+
+/// Result of one input byte.
+enum Step {
+    Ignored,
+    /// A byte was added at `len - 1`.
+    Added,
+    Erased,
+    Line,
+    Overflow,
+}
+
+/// Reader accumulating bytes into lines of up to `N` bytes.
+///
+/// Lines are ended by CR, LF or CRLF. Backspace (0x08) and DEL (0x7F) erase the last character,
+/// and other control characters except tab are ignored.
+pub struct LineReader<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+    /// The previous byte was CR, so that LF of CRLF is skipped.
+    last_cr: bool,
+    overflow: bool,
+    /// A line was returned; it is cleared at the next read.
+    ready: bool,
+}
+
+impl<const N: usize> LineReader<N> {
+    /// Initialize LineReader.
+    pub const fn new() -> Self {
+        Self { buf: [0u8; N], len: 0, last_cr: false, overflow: false, ready: false }
+    }
+    /// Discard the bytes of the current line.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.overflow = false;
+        self.ready = false;
+    }
+    /// Bytes of the current, unfinished line.
+    pub fn pending(&self) -> &[u8] {
+        if self.ready {
+            return &[];
+        }
+        &self.buf[..self.len]
+    }
+    fn feed(&mut self, b: u8) -> Step {
+        if self.ready {
+            self.clear();
+        }
+        let last_cr = self.last_cr;
+        self.last_cr = b == b'\r';
+        match b {
+            b'\n' if last_cr => Step::Ignored,
+            b'\r' | b'\n' => {
+                if self.overflow {
+                    self.clear();
+                    return Step::Overflow;
+                }
+                self.ready = true;
+                Step::Line
+            }
+            0x08 | 0x7F => {
+                if self.overflow || self.len == 0 {
+                    return Step::Ignored;
+                }
+                // Remove the continuation bytes and the leading byte of the last character.
+                while self.len > 0 {
+                    self.len -= 1;
+                    if self.buf[self.len] & 0xC0 != 0x80 {
+                        break;
+                    }
+                }
+                Step::Erased
+            }
+            b'\t' | 0x20..=0x7E | 0x80..=0xFF => {
+                if self.overflow || self.len >= N {
+                    self.overflow = true;
+                    return Step::Ignored;
+                }
+                self.buf[self.len] = b;
+                self.len += 1;
+                Step::Added
+            }
+            _ => Step::Ignored,
+        }
+    }
+    fn line(&self) -> Result<Option<&str>, ReadError> {
+        match core::str::from_utf8(&self.buf[..self.len]) {
+            Ok(line) => Ok(Some(line)),
+            Err(_) => Err(ReadError::Utf8),
+        }
+    }
+    /// The last character of the line if it was just completed by the added byte.
+    fn completed_char(&self) -> Option<&str> {
+        let start = self.buf[..self.len].iter().rposition(|&b| b & 0xC0 != 0x80)?;
+        core::str::from_utf8(&self.buf[start..self.len]).ok()
+    }
+    /// Read the available bytes from `src` until a line is completed.
+    ///
+    /// Returns the line without its line ending, or `None` if no complete line is available yet.
+    /// The line is kept until the next call.
+    pub fn read_line<S: Source + ?Sized>(&mut self, src: &mut S) -> Result<Option<&str>, ReadError> {
+        loop {
+            let mut b = [0u8; 1];
+            match src.read(&mut b) {
+                Ok(0) | Err(UsbError::WouldBlock) => { return Ok(None); }
+                Ok(_) => {}
+                Err(e) => { return Err(e.into()); }
+            }
+            match self.feed(b[0]) {
+                Step::Line => { return self.line(); }
+                Step::Overflow => { return Err(ReadError::Overflow); }
+                _ => {}
+            }
+        }
+    }
+    /// Same as `read_line`, and echo the input back through `writer`, so that it is visible on a terminal.
+    ///
+    /// Erased characters are echoed as "\x08 \x08", and line endings as "\r\n".
+    /// The echo is best effort: output which the sink cannot take is dropped.
+    pub fn read_line_echo<S: Source + Sink + ?Sized>(&mut self, writer: &mut Writer, serial: &mut S) -> Result<Option<&str>, ReadError> {
+        loop {
+            let mut b = [0u8; 1];
+            match serial.read(&mut b) {
+                Ok(0) | Err(UsbError::WouldBlock) => { return Ok(None); }
+                Ok(_) => {}
+                Err(e) => { return Err(e.into()); }
+            }
+            match self.feed(b[0]) {
+                Step::Ignored => {}
+                Step::Added => {
+                    if let Some(c) = self.completed_char() {
+                        writer.write_str(c, serial).ok();
+                    }
+                }
+                Step::Erased => { writer.write_str("\x08 \x08", serial).ok(); }
+                Step::Line => {
+                    writer.write_str("\r\n", serial).ok();
+                    return self.line();
+                }
+                Step::Overflow => {
+                    writer.write_str("\r\n", serial).ok();
+                    return Err(ReadError::Overflow);
+                }
+            }
+        }
+    }
+}
+
+impl<const N: usize> Default for LineReader<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
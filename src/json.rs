@@ -0,0 +1,310 @@
+//! Streaming JSON output without allocation.
+//!
+//! Nesting and comma placement are checked with a stack of `D` levels.
+//!
+//! ```ignore
+//! let mut json = JsonWriter::<4>::new();
+//! json.begin_object(&mut serial)?;
+//! json.key("temp", &mut serial)?;
+//! json.value_f32(23.456, 2, &mut serial)?;
+//! json.key("ok", &mut serial)?;
+//! json.value_bool(true, &mut serial)?;
+//! json.end_object(&mut serial)?;
+//! // "{\"temp\":23.46,\"ok\":true}\r\n"
+//! json.end_line(&mut serial)?;
+//! ```
+
+use usb_device::class_prelude::UsbError;
+
+use crate::{Sink, Writer};
+
+/// Errors of `JsonWriter`. Nothing is output when the structure is invalid.
+#[derive(Debug)]
+pub enum JsonError {
+    /// Error of the underlying sink.
+    Usb(UsbError),
+    /// Nesting is deeper than the stack of `JsonWriter`.
+    TooDeep,
+    /// A value was output in an object without a key.
+    KeyExpected,
+    /// A key was output outside of an object or right after another key,
+    /// or an object was ended right after a key.
+    ValueExpected,
+    /// An object or array was ended while the other kind (or nothing) was open.
+    Mismatched,
+    /// The line was ended while an object or array was open, or before any value.
+    Incomplete,
+    /// A value was output after the top-level value of the line was completed.
+    Complete,
+}
+
+impl From<UsbError> for JsonError {
+    fn from(e: UsbError) -> Self {
+        JsonError::Usb(e)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Level {
+    object: bool,
+    has_items: bool,
+    after_key: bool,
+}
+
+macro_rules! value_int {
+    ($(#[$meta: meta])*
+    $int: ty, $name: ident, $f: ident) => {
+        $(#[$meta])*
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: $int, serial: &mut S) -> Result<usize, (JsonError, usize)> {
+            let mut count = 0;
+            handle_err2!(self.start_value(serial), count);
+            handle_err2!(self.writer.$f(val, serial), count);
+            self.end_value();
+            Ok(count)
+        }
+    };
+}
+macro_rules! value_float {
+    ($(#[$meta: meta])*
+    $float: ty, $name: ident, $f: ident) => {
+        $(#[$meta])*
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: $float, nodp: usize, serial: &mut S) -> Result<usize, (JsonError, usize)> {
+            let mut count = 0;
+            handle_err2!(self.start_value(serial), count);
+            if val.is_finite() {
+                handle_err2!(self.writer.$f(val, nodp, serial), count);
+            } else {
+                handle_err2!(self.writer.write_str("null", serial), count);
+            }
+            self.end_value();
+            Ok(count)
+        }
+    };
+}
+
+/// Writer of JSON values nested up to `D` levels.
+pub struct JsonWriter<const D: usize> {
+    writer: Writer,
+    stack: [Level; D],
+    depth: usize,
+    complete: bool,
+}
+
+impl<const D: usize> JsonWriter<D> {
+    /// Initialize JsonWriter.
+    pub const fn new() -> Self {
+        Self {
+            writer: Writer::new(),
+            stack: [Level { object: false, has_items: false, after_key: false }; D],
+            depth: 0,
+            complete: false,
+        }
+    }
+    /// Current nesting depth.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+    /// Check that a value can be output here, and output a comma if necessary.
+    fn start_value<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (JsonError, usize)> {
+        if self.depth == 0 {
+            if self.complete {
+                return Err((JsonError::Complete, 0));
+            }
+            return Ok(0);
+        }
+        let level = &mut self.stack[self.depth - 1];
+        if level.object {
+            if !level.after_key {
+                return Err((JsonError::KeyExpected, 0));
+            }
+            level.after_key = false;
+            return Ok(0);
+        }
+        let comma = level.has_items;
+        level.has_items = true;
+        if comma {
+            return write_comma(serial);
+        }
+        Ok(0)
+    }
+    fn end_value(&mut self) {
+        if self.depth == 0 {
+            self.complete = true;
+        }
+    }
+    /// Output a key of the current object.
+    pub fn key<S: Sink + ?Sized>(&mut self, key: &str, serial: &mut S) -> Result<usize, (JsonError, usize)> {
+        if self.depth == 0 {
+            return Err((JsonError::ValueExpected, 0));
+        }
+        let level = &mut self.stack[self.depth - 1];
+        if !level.object || level.after_key {
+            return Err((JsonError::ValueExpected, 0));
+        }
+        let comma = level.has_items;
+        level.has_items = true;
+        level.after_key = true;
+        let mut count = 0;
+        if comma {
+            handle_err2!(write_comma(serial), count);
+        }
+        handle_err2!(write_string(&mut self.writer, key, serial), count);
+        handle_err2!(self.writer.write_str(":", serial), count);
+        Ok(count)
+    }
+    fn begin<S: Sink + ?Sized>(&mut self, object: bool, serial: &mut S) -> Result<usize, (JsonError, usize)> {
+        if self.depth >= D {
+            return Err((JsonError::TooDeep, 0));
+        }
+        let mut count = 0;
+        handle_err2!(self.start_value(serial), count);
+        self.stack[self.depth] = Level { object, has_items: false, after_key: false };
+        self.depth += 1;
+        handle_err2!(self.writer.write_str(if object { "{" } else { "[" }, serial), count);
+        Ok(count)
+    }
+    fn end<S: Sink + ?Sized>(&mut self, object: bool, serial: &mut S) -> Result<usize, (JsonError, usize)> {
+        if self.depth == 0 || self.stack[self.depth - 1].object != object {
+            return Err((JsonError::Mismatched, 0));
+        }
+        if self.stack[self.depth - 1].after_key {
+            return Err((JsonError::ValueExpected, 0));
+        }
+        self.depth -= 1;
+        self.end_value();
+        let mut count = 0;
+        handle_err2!(self.writer.write_str(if object { "}" } else { "]" }, serial), count);
+        Ok(count)
+    }
+    /// Begin an object.
+    pub fn begin_object<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (JsonError, usize)> {
+        self.begin(true, serial)
+    }
+    /// End the current object.
+    pub fn end_object<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (JsonError, usize)> {
+        self.end(true, serial)
+    }
+    /// Begin an array.
+    pub fn begin_array<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (JsonError, usize)> {
+        self.begin(false, serial)
+    }
+    /// End the current array.
+    pub fn end_array<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (JsonError, usize)> {
+        self.end(false, serial)
+    }
+    /// Output a string value, escaping `"`, `\` and control characters.
+    pub fn value_str<S: Sink + ?Sized>(&mut self, val: &str, serial: &mut S) -> Result<usize, (JsonError, usize)> {
+        let mut count = 0;
+        handle_err2!(self.start_value(serial), count);
+        handle_err2!(write_string(&mut self.writer, val, serial), count);
+        self.end_value();
+        Ok(count)
+    }
+    /// Output `true` or `false`.
+    pub fn value_bool<S: Sink + ?Sized>(&mut self, val: bool, serial: &mut S) -> Result<usize, (JsonError, usize)> {
+        let mut count = 0;
+        handle_err2!(self.start_value(serial), count);
+        handle_err2!(self.writer.write_str(if val { "true" } else { "false" }, serial), count);
+        self.end_value();
+        Ok(count)
+    }
+    /// Output `null`.
+    pub fn value_null<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (JsonError, usize)> {
+        let mut count = 0;
+        handle_err2!(self.start_value(serial), count);
+        handle_err2!(self.writer.write_str("null", serial), count);
+        self.end_value();
+        Ok(count)
+    }
+    value_int!(/// Output an `i8` value.
+        i8, value_i8, write_i8);
+    value_int!(/// Output an `i16` value.
+        i16, value_i16, write_i16);
+    value_int!(/// Output an `i32` value.
+        i32, value_i32, write_i32);
+    value_int!(/// Output an `i64` value.
+        i64, value_i64, write_i64);
+    value_int!(/// Output an `isize` value.
+        isize, value_isize, write_isize);
+    value_int!(/// Output a `u8` value.
+        u8, value_u8, write_u8);
+    value_int!(/// Output a `u16` value.
+        u16, value_u16, write_u16);
+    value_int!(/// Output a `u32` value.
+        u32, value_u32, write_u32);
+    value_int!(/// Output a `u64` value.
+        u64, value_u64, write_u64);
+    value_int!(/// Output a `usize` value.
+        usize, value_usize, write_usize);
+    value_float!(/// Output an `f32` value to `nodp` decimal places.
+        /// NaN and infinities are output as `null`.
+        f32, value_f32, write_f32);
+    value_float!(/// Output an `f64` value to `nodp` decimal places.
+        /// NaN and infinities are output as `null`.
+        f64, value_f64, write_f64);
+    /// Break the line after the completed top-level value, and get ready for the next one.
+    pub fn end_line<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (JsonError, usize)> {
+        if self.depth > 0 || !self.complete {
+            return Err((JsonError::Incomplete, 0));
+        }
+        self.complete = false;
+        let mut count = 0;
+        handle_err2!(self.writer.write_str("\r\n", serial), count);
+        Ok(count)
+    }
+    /// Discard the state, e.g. after an error of the sink, so that a new line can be started.
+    pub fn reset(&mut self) {
+        self.depth = 0;
+        self.complete = false;
+    }
+}
+
+impl<const D: usize> Default for JsonWriter<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn write_comma<S: Sink + ?Sized>(serial: &mut S) -> Result<usize, (JsonError, usize)> {
+    match serial.write(b",") {
+        Ok(n) => Ok(n),
+        Err(e) => Err((e.into(), 0)),
+    }
+}
+
+/// Output `val` as a JSON string.
+fn write_string<S: Sink + ?Sized>(writer: &mut Writer, val: &str, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+    let mut count = 0;
+    handle_err2!(writer.write_str("\"", serial), count);
+    let mut start = 0;
+    for (i, b) in val.bytes().enumerate() {
+        let escape = match b {
+            b'"' => "\\\"",
+            b'\\' => "\\\\",
+            b'\n' => "\\n",
+            b'\r' => "\\r",
+            b'\t' => "\\t",
+            0x08 => "\\b",
+            0x0C => "\\f",
+            0x00..=0x1F => "",
+            _ => continue,
+        };
+        if start < i {
+            handle_err2!(writer.write_str(&val[start..i], serial), count);
+        }
+        if escape.is_empty() {
+            handle_err2!(writer.write_str("\\u00", serial), count);
+            handle_err2!(writer.write_str(if b < 0x10 { "0" } else { "1" }, serial), count);
+            let digit = [b"0123456789abcdef"[(b & 0xF) as usize]];
+            handle_err1!(serial.write(&digit), count);
+        } else {
+            handle_err2!(writer.write_str(escape, serial), count);
+        }
+        start = i + 1;
+    }
+    if start < val.len() {
+        handle_err2!(writer.write_str(&val[start..], serial), count);
+    }
+    handle_err2!(writer.write_str("\"", serial), count);
+    Ok(count)
+}
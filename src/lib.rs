@@ -1,37 +1,76 @@
 #![no_std]
 
-use usb_device::class_prelude::{UsbBus, UsbError};
-use usbd_serial::SerialPort;
+use core::fmt;
+use usb_device::class_prelude::UsbError;
 use numtoa::NumToA;
 
-const F32_EXP: [f32; 8] = [
-    1f32, 10f32, 100f32, 1_000f32, 10_000f32, 100_000f32, 1_000_000f32, 10_000_000f32
-];
-const F64_EXP: [f64; 16] = [
-    1f64, 10f64, 100f64, 1_000f64, 10_000f64, 100_000f64, 1_000_000f64, 10_000_000f64,
-    100_000_000f64, 1_000_000_000f64, 10_000_000_000f64, 100_000_000_000f64, 1_000_000_000_000f64, 
-    10_000_000_000_000f64, 100_000_000_000_000f64, 1_000_000_000_000_000f64
-];
-const U64_BASE: u64 = 10_000_000_000_000_000_000;
+pub use ring::RingBuffer;
+pub use sink::{SliceSink, Sink, Source, UsbSerial};
 
 /// Output structure containing a buffer for `numtoa` crate.
+///
+/// Optionally, a prefix of a timestamp, a level marker and a tag (e.g. `[   12.345678] [WARN imu] `)
+/// is output at the start of each line.
 pub struct Writer {
-    buf: [u8; 20]
+    buf: [u8; 20],
+    tag: Option<&'static str>,
+    level: Option<Level>,
+    clock: Option<fn() -> u64>,
+    line_start: bool,
 }
 
-macro_rules! handle_err0 {
-    ($f: expr) => {
-        match $f {
-            Ok(n) => { Ok(n) }
-            Err(e) => { return Err((e, 0)); }
+/// Level marker of the line prefix of `Writer`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    /// Marker output in the line prefix, e.g. "WARN".
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
         }
-    };
+    }
+}
+
+/// Bridges `core::fmt::Write` to a `Sink` while counting the output bytes.
+struct FmtAdapter<'a, S: Sink + ?Sized> {
+    serial: &'a mut S,
+    count: usize,
+    err: Option<UsbError>,
 }
+
+impl<S: Sink + ?Sized> fmt::Write for FmtAdapter<'_, S> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        // `fmt::Write` cannot report a short write, so the rest is retried.
+        match sink::write_all(self.serial, s.as_bytes()) {
+            Ok(n) => {
+                self.count += n;
+                Ok(())
+            }
+            Err((e, n)) => {
+                self.count += n;
+                self.err = Some(e);
+                Err(fmt::Error)
+            }
+        }
+    }
+}
+
 macro_rules! handle_err1 {
     ($f: expr, $count: ident) => {
         match $f {
             Ok(n) => { $count += n; }
-            Err(e) => { return Err((e, $count)); }
+            Err(e) => { return Err((e.into(), $count)); }
         }
     };
 }
@@ -39,7 +78,7 @@ macro_rules! handle_err2 {
     ($f: expr, $count: ident) => {
         match $f {
             Ok(n) => { $count += n; }
-            Err((e, n)) => { return Err((e, $count+n)); }
+            Err((e, n)) => { return Err((e.into(), $count+n)); }
         }
     };
 }
@@ -48,90 +87,37 @@ macro_rules! write_int {
     ($(#[$meta: meta])*
     $int: ty, $name: ident) => {
         $(#[$meta])*
-        pub fn $name<U: UsbBus>(&mut self, val: $int, serial: &mut SerialPort<U>) -> Result<usize, (UsbError, usize)> {
-            handle_err0!(serial.write(val.numtoa(10, &mut self.buf)))
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: $int, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+            let mut count = 0;
+            handle_err2!(self._prefix(serial), count);
+            handle_err1!(serial.write(val.numtoa(10, &mut self.buf)), count);
+            Ok(count)
         }
     };
 }
-macro_rules! write_float {
+macro_rules! write_hex {
     ($(#[$meta: meta])*
-    $float: ty, $name: ident, $nodp_lim: expr, $exp: ident) => {
+    $int: ty, $name: ident) => {
         $(#[$meta])*
-        pub fn $name<U: UsbBus>(&mut self, val: $float, nodp: usize, serial: &mut SerialPort<U>) -> Result<usize, (UsbError, usize)> {
-            let mut f = val;
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: $int, serial: &mut S) -> Result<usize, (UsbError, usize)> {
             let mut count = 0;
-            if f < 0.0 {
-                f = -f;
-                handle_err2!(self.write_str("-", serial), count)
-            }
-            let mut z_count_base = 0;
-            while f > U64_BASE as $float * U64_BASE as $float {
-                f /= U64_BASE as $float;
-                z_count_base += 1;
-            }
-            let mut z_count = 0;
-            while f > U64_BASE as $float {
-                f /= 10.0;
-                z_count += 1;
-            }
-            let int = f as u64;
-            handle_err2!(self.write_u64(int, serial), count);
-            for _ in 0..z_count_base {
-                handle_err2!(self.write_str("0000000000000000000", serial), count);
-            }
-            for _ in 0..z_count {
-                handle_err2!(self.write_str("0", serial), count);
-            }
-            if nodp == 0 { return Ok(count); }
-            let nodp = if nodp > $nodp_lim { $nodp_lim } else { nodp };
-            let frac = ((f - int as $float) * $exp[nodp]) as u64;
-            handle_err2!(self.write_str(".", serial), count);
-            let mut nodp = nodp;
-            while nodp > 1 && frac as $float < $exp[nodp - 1] {
+            let digits = (<$int>::BITS / 4) as usize;
+            let zeros = (val.leading_zeros() / 4) as usize;
+            handle_err2!(self.write_str("0x", serial), count);
+            for _ in 0..(if zeros < digits { zeros } else { digits - 1 }) {
                 handle_err2!(self.write_str("0", serial), count);
-                nodp -= 1;
             }
-            handle_err2!(self.write_u64(frac, serial), count);
+            handle_err1!(serial.write(val.numtoa(16, &mut self.buf)), count);
             Ok(count)
         }
     };
 }
-macro_rules! write_float_exp {
+macro_rules! write_float {
     ($(#[$meta: meta])*
-    $float: ty, $name: ident, $nodp_lim: expr, $exp: ident, $f: ident) => {
+    $float: ty, $name: ident, $limbs: expr, $f: ident) => {
         $(#[$meta])*
-        pub fn $name<U: UsbBus>(&mut self, val: $float, nodp: usize, serial: &mut SerialPort<U>) -> Result<usize, (UsbError, usize)> {
-            let mut f = val;
-            let mut count = 0;
-            let mut exp: i16 = 0;
-            if f < 0.0 {
-                f = -f;
-                handle_err2!(self.write_str("-", serial), count)
-            } else {
-                handle_err2!(self.write_str(" ", serial), count)
-            }
-            while f >= 10.0 {
-                exp += 1;
-                f /= 10.0;
-            }
-            while f < 1.0 {
-                exp -= 1;
-                f *= 10.0;
-            }
-            handle_err2!(self.$f(f, nodp, serial), count);
-            handle_err2!(self.write_str("e", serial), count);
-            if exp < 0 {
-                handle_err2!(self.write_str("-", serial), count);
-                exp = -exp;
-                if exp < 10 {
-                    handle_err2!(self.write_str("0", serial), count);
-                }
-            } else {
-                if exp < 100 { handle_err2!(self.write_str("0", serial), count); }
-                if exp < 10 { handle_err2!(self.write_str("0", serial), count); }
-            }
-            handle_err2!(self.write_i16(exp, serial), count);
-            Ok(count)
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: $float, nodp: usize, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+            self.$f::<$float, S, { $limbs }>(val, nodp, serial)
         }
     };
 }
@@ -139,7 +125,7 @@ macro_rules! write_int_slice {
     ($(#[$meta: meta])*
     $int: ty, $name: ident, $f: ident) => {
         $(#[$meta])*
-        pub fn $name<U: UsbBus>(&mut self, val: &[$int], serial: &mut SerialPort<U>) -> Result<usize, (UsbError, usize)> {
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: &[$int], serial: &mut S) -> Result<usize, (UsbError, usize)> {
             let mut count = 0;
             handle_err2!(self.write_str("[ ", serial), count);
             for &val in val.iter() {
@@ -155,7 +141,7 @@ macro_rules! write_float_slice {
     ($(#[$meta: meta])*
     $float: ty, $name: ident, $f: ident) => {
         $(#[$meta])*
-        pub fn $name<U: UsbBus>(&mut self, val: &[$float], nodp: usize, serial: &mut SerialPort<U>) -> Result<usize, (UsbError, usize)> {
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: &[$float], nodp: usize, serial: &mut S) -> Result<usize, (UsbError, usize)> {
             let mut count = 0;
             handle_err2!(self.write_str("[ ", serial), count);
             for &val in val.iter() {
@@ -171,7 +157,7 @@ macro_rules! writeln_str_int {
     ($(#[$meta: meta])*
     $type: ty, $name: ident, $f: ident) => {
         $(#[$meta])*
-        pub fn $name<U: UsbBus>(&mut self, val: $type, serial: &mut SerialPort<U>) -> Result<usize, (UsbError, usize)> {
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: $type, serial: &mut S) -> Result<usize, (UsbError, usize)> {
             let mut count = 0;
             handle_err2!(self.$f(val, serial), count);
             handle_err1!(self._writeln(serial), count);
@@ -183,7 +169,7 @@ macro_rules! writeln_float {
     ($(#[$meta: meta])*
     $type: ty, $name: ident, $f: ident) => {
         $(#[$meta])*
-        pub fn $name<U: UsbBus>(&mut self, val: $type, nodp: usize, serial: &mut SerialPort<U>) -> Result<usize, (UsbError, usize)> {
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: $type, nodp: usize, serial: &mut S) -> Result<usize, (UsbError, usize)> {
             let mut count = 0;
             handle_err2!(self.$f(val, nodp, serial), count);
             handle_err1!(self._writeln(serial), count);
@@ -192,21 +178,162 @@ macro_rules! writeln_float {
     };
 }
 
+mod float;
+mod ring;
+mod sink;
+pub mod ansi;
+pub mod cbor;
+pub mod chart;
+pub mod crc;
+pub mod csv;
+pub mod dashboard;
+pub mod display;
+pub mod editor;
+pub mod format;
+pub mod json;
+pub mod parse;
+pub mod frame;
+pub mod framing;
+pub mod input;
+pub mod plot;
+pub mod progress;
+pub mod shell;
+pub mod table;
+#[cfg(feature = "log")]
+pub mod logger;
+#[cfg(feature = "defmt")]
+pub mod defmt_logger;
+#[cfg(feature = "panic-serial")]
+pub mod panic;
+#[cfg(feature = "hardfault")]
+pub mod hardfault;
+
 impl Writer {
     /// Initialize Writer.
-    pub fn new() -> Self {
-        Self { buf: [0u8; 20] }
+    pub const fn new() -> Self {
+        Self { buf: [0u8; 20], tag: None, level: None, clock: None, line_start: true }
+    }
+    /// Set the tag of the line prefix.
+    pub const fn with_tag(mut self, tag: &'static str) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+    /// Set the level marker of the line prefix.
+    pub const fn with_level(mut self, level: Level) -> Self {
+        self.level = Some(level);
+        self
+    }
+    /// Prefix each line with the time returned by `clock` in microseconds, as `[   12.345678] `.
+    pub const fn with_clock(mut self, clock: fn() -> u64) -> Self {
+        self.clock = Some(clock);
+        self
     }
-    fn _writeln<U: UsbBus>(&self, serial: &mut SerialPort<U>) -> Result<usize, UsbError> {
-        serial.write(b"\r\n")
+    /// Change the tag of the line prefix, or remove it with `None`.
+    pub fn set_tag(&mut self, tag: Option<&'static str>) {
+        self.tag = tag;
+    }
+    /// Change the level marker of the line prefix, or remove it with `None`.
+    pub fn set_level(&mut self, level: Option<Level>) {
+        self.level = level;
+    }
+    /// Change the clock of the line prefix, or remove the timestamp with `None`.
+    pub fn set_clock(&mut self, clock: Option<fn() -> u64>) {
+        self.clock = clock;
+    }
+    fn _writeln<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, UsbError> {
+        let n = serial.write(b"\r\n")?;
+        self.line_start = true;
+        Ok(n)
+    }
+    /// Output the line prefix if nothing has been output on the current line yet.
+    fn _prefix<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        if !self.line_start {
+            return Ok(0);
+        }
+        self.line_start = false;
+        let mut count = 0;
+        if let Some(clock) = self.clock {
+            handle_err2!(self.write_timestamp(clock(), serial), count);
+            handle_err2!(self.write_str(" ", serial), count);
+        }
+        match (self.level, self.tag) {
+            (Some(level), Some(tag)) => {
+                handle_err2!(self.write_str("[", serial), count);
+                handle_err2!(self.write_str(level.as_str(), serial), count);
+                handle_err2!(self.write_str(" ", serial), count);
+                handle_err2!(self.write_str(tag, serial), count);
+                handle_err2!(self.write_str("] ", serial), count);
+            }
+            (Some(level), None) => {
+                handle_err2!(self.write_str("[", serial), count);
+                handle_err2!(self.write_str(level.as_str(), serial), count);
+                handle_err2!(self.write_str("] ", serial), count);
+            }
+            (None, Some(tag)) => {
+                handle_err2!(self.write_str("[", serial), count);
+                handle_err2!(self.write_str(tag, serial), count);
+                handle_err2!(self.write_str("] ", serial), count);
+            }
+            (None, None) => {}
+        }
+        Ok(count)
     }
     /// Output `&str`.
-    pub fn write_str<U: UsbBus>(&self, str: &str, serial: &mut SerialPort<U>) -> Result<usize, (UsbError, usize)> {
-        match serial.write(str.as_bytes()) {
-            Ok(n) => Ok(n),
-            Err(e) => Err((e, 0))
+    ///
+    /// The line prefix is also output after each '\n' of `str`, once the '\n' has been written.
+    pub fn write_str<S: Sink + ?Sized>(&mut self, str: &str, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        for line in str.split_inclusive('\n') {
+            handle_err2!(self._prefix(serial), count);
+            let n = match serial.write(line.as_bytes()) {
+                Ok(n) => n,
+                Err(e) => { return Err((e, count)); }
+            };
+            count += n;
+            if n < line.len() {
+                // A short write stops before the '\n', so the line goes on.
+                return Ok(count);
+            }
+            if line.ends_with('\n') {
+                self.line_start = true;
+            }
+        }
+        Ok(count)
+    }
+    /// Output formatted arguments, e.g. `format_args!("{}: {}", name, val)`.
+    pub fn write_args<S: Sink + ?Sized>(&mut self, args: fmt::Arguments, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        handle_err2!(self._prefix(serial), count);
+        let mut adapter = FmtAdapter { serial, count: 0, err: None };
+        match fmt::write(&mut adapter, args) {
+            Ok(()) => Ok(count + adapter.count),
+            Err(_) => Err((adapter.err.unwrap_or(UsbError::InvalidState), count + adapter.count))
         }
     }
+    /// Output `us` microseconds as seconds, in the form of `[   12.345678]`.
+    pub fn write_timestamp<S: Sink + ?Sized>(&mut self, us: u64, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        let secs = us / 1_000_000;
+        let micros = us % 1_000_000;
+        handle_err2!(self.write_str("[", serial), count);
+        let mut width = 1;
+        while width < 5 && secs >= 10u64.pow(width) {
+            width += 1;
+        }
+        for _ in width..5 {
+            handle_err2!(self.write_str(" ", serial), count);
+        }
+        handle_err2!(self.write_u64(secs, serial), count);
+        handle_err2!(self.write_str(".", serial), count);
+        let mut digits = 5;
+        while digits > 0 && micros < 10u64.pow(digits) {
+            handle_err2!(self.write_str("0", serial), count);
+            digits -= 1;
+        }
+        handle_err2!(self.write_u64(micros, serial), count);
+        handle_err2!(self.write_str("]", serial), count);
+        Ok(count)
+    }
     write_int!(/// Output `i8`.
         i8, write_i8);
     write_int!(/// Output `i16`.
@@ -227,14 +354,32 @@ impl Writer {
         u64, write_u64);
     write_int!(/// Output `usize`.
         usize, write_usize);
+    write_hex!(/// Output `u8` in hexadecimal, zero-padded to 2 digits (e.g. "0x0A").
+        u8, write_u8_hex);
+    write_hex!(/// Output `u16` in hexadecimal, zero-padded to 4 digits (e.g. "0x00FF").
+        u16, write_u16_hex);
+    write_hex!(/// Output `u32` in hexadecimal, zero-padded to 8 digits (e.g. "0x20001FF0").
+        u32, write_u32_hex);
+    write_hex!(/// Output `u64` in hexadecimal, zero-padded to 16 digits.
+        u64, write_u64_hex);
+    write_hex!(/// Output `usize` in hexadecimal, zero-padded to the width of `usize`.
+        usize, write_usize_hex);
     write_float!(/// Output `f32` to `nodp` decimal places.
-        f32, write_f32, 7, F32_EXP);
+        /// The exact value is rounded half to even, as by `{:.nodp}` of `core::fmt`.
+        /// At most 41 + nodp bytes are output, besides the line prefix.
+        f32, write_f32, float::F32_LIMBS, _write_fixed);
     write_float!(/// Output `f64` to `nodp` decimal places.
-        f64, write_f64, 15, F64_EXP);
-    write_float_exp!(/// Output `f32` to `nodp` decimal places in exponential format.
-        f32, write_f32_exp, 7, F32_EXP, write_f32);
-    write_float_exp!(/// Output `f64` to `nodp` decimal places in exponential format.
-        f64, write_f64_exp, 15, F64_EXP, write_f64);
+        /// The exact value is rounded half to even, as by `{:.nodp}` of `core::fmt`.
+        /// At most 311 + nodp bytes are output, besides the line prefix.
+        f64, write_f64, float::F64_LIMBS, _write_fixed);
+    write_float!(/// Output `f32` to `nodp` decimal places in exponential format (e.g. " 1.50e-03", "-2.00e010").
+        /// The exact value is rounded half to even, as by `{:.nodp e}` of `core::fmt`.
+        /// At most 7 + nodp bytes are output, besides the line prefix.
+        f32, write_f32_exp, float::F32_LIMBS, _write_exp);
+    write_float!(/// Output `f64` to `nodp` decimal places in exponential format (e.g. " 1.50e-03", "-2.00e010").
+        /// The exact value is rounded half to even, as by `{:.nodp e}` of `core::fmt`.
+        /// At most 8 + nodp bytes are output, besides the line prefix.
+        f64, write_f64_exp, float::F64_LIMBS, _write_exp);
     write_int_slice!(/// Output `&[i8]`.
         i8, write_i8_slice, write_i8);
     write_int_slice!(/// Output `&[i16]`.
@@ -359,4 +504,9 @@ impl Writer {
         /// Each element is output to `nodp` decimal places in exponential format.
         /// Then break the line.
         &[f64], writeln_f64_slice_exp, write_f64_slice_exp);
-}
\ No newline at end of file
+}
+impl Default for Writer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
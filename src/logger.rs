@@ -0,0 +1,170 @@
+//! Backend for the `log` crate (requires the `log` feature).
+//!
+//! Records are formatted as `[LEVEL target] message\r\n` into an internal `RingBuffer`,
+//! so `log::info!` etc. can be called from anywhere, including interrupt handlers.
+//! Call `SerialLogger::drain` regularly (e.g. after `usb_dev.poll`) to send them to the serial port.
+//!
+//! ```ignore
+//! static LOGGER: SerialLogger<1024> = SerialLogger::new()
+//!     .with_max_level(LevelFilter::Info)
+//!     .with_targets(&[("usb_device", LevelFilter::Warn)])
+//!     .with_clock(uptime_us);
+//!
+//! LOGGER.init();
+//! loop {
+//!     let _ = usb_dev.poll(&mut [&mut serial]);
+//!     let _ = LOGGER.drain(&mut serial);
+//! }
+//! ```
+
+use core::cell::RefCell;
+use critical_section::Mutex;
+use log::{LevelFilter, Log, Metadata, Record};
+use usb_device::class_prelude::UsbError;
+
+use crate::{RingBuffer, Sink, Writer};
+
+struct State<const N: usize> {
+    buf: RingBuffer<N>,
+    writer: Writer,
+    dropped: usize,
+}
+
+/// `log::Log` implementation buffering `N` bytes of formatted records.
+pub struct SerialLogger<const N: usize> {
+    state: Mutex<RefCell<State<N>>>,
+    max_level: LevelFilter,
+    targets: &'static [(&'static str, LevelFilter)],
+    clock: Option<fn() -> u64>,
+}
+
+impl<const N: usize> SerialLogger<N> {
+    /// Initialize SerialLogger which outputs records of all levels without timestamps.
+    pub const fn new() -> Self {
+        Self {
+            state: Mutex::new(RefCell::new(State {
+                buf: RingBuffer::new(),
+                writer: Writer::new(),
+                dropped: 0,
+            })),
+            max_level: LevelFilter::Trace,
+            targets: &[],
+            clock: None,
+        }
+    }
+    /// Set the maximum level of records to output.
+    pub const fn with_max_level(mut self, level: LevelFilter) -> Self {
+        self.max_level = level;
+        self
+    }
+    /// Set the maximum levels for individual targets.
+    ///
+    /// A filter applies to its target and to the modules below it (`"app::imu"` also covers `"app::imu::spi"`).
+    /// The longest matching filter is used instead of the maximum level set by `with_max_level`.
+    pub const fn with_targets(mut self, targets: &'static [(&'static str, LevelFilter)]) -> Self {
+        self.targets = targets;
+        self
+    }
+    /// Prefix each record with the uptime returned by `clock` in microseconds, as `[   12.345678] `.
+    pub const fn with_clock(mut self, clock: fn() -> u64) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+    /// Register as the logger of the `log` crate.
+    ///
+    /// This should be called once at start-up.
+    /// Targets without atomic compare-and-swap (e.g. RP2040) register the logger inside a critical section.
+    pub fn init(&'static self) {
+        let level = self.targets.iter().fold(self.max_level, |max, &(_, level)| {
+            if level > max { level } else { max }
+        });
+        critical_section::with(|_| unsafe {
+            let _ = log::set_logger_racy(self);
+            log::set_max_level_racy(level);
+        });
+    }
+    /// Output the buffered records to `serial`.
+    /// Returns the number of bytes output; the rest is kept until the next call.
+    pub fn drain<S: Sink + ?Sized>(&self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        critical_section::with(|cs| self.state.borrow_ref_mut(cs).buf.drain(serial))
+    }
+    /// Number of records discarded so far because the buffer was full.
+    pub fn dropped(&self) -> usize {
+        critical_section::with(|cs| self.state.borrow_ref(cs).dropped)
+    }
+    fn level_for(&self, target: &str) -> LevelFilter {
+        let mut best: Option<(usize, LevelFilter)> = None;
+        for &(name, level) in self.targets.iter() {
+            let matched = target == name
+                || (target.starts_with(name) && target[name.len()..].starts_with("::"));
+            let longer = match best {
+                Some((len, _)) => name.len() > len,
+                None => true,
+            };
+            if matched && longer {
+                best = Some((name.len(), level));
+            }
+        }
+        match best {
+            Some((_, level)) => level,
+            None => self.max_level,
+        }
+    }
+}
+
+impl<const N: usize> Default for SerialLogger<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Rejects writes which do not fit entirely, so that a record is either complete or detected as cut off.
+struct Whole<'a, const N: usize>(&'a mut RingBuffer<N>);
+
+impl<const N: usize> Sink for Whole<'_, N> {
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        if data.len() > self.0.free() {
+            return Err(UsbError::BufferOverflow);
+        }
+        Ok(self.0.push(data))
+    }
+}
+
+fn write_record<const N: usize>(state: &mut State<N>, record: &Record, clock: Option<fn() -> u64>) -> Result<usize, (UsbError, usize)> {
+    let mut count = 0;
+    let buf = &mut Whole(&mut state.buf);
+    let writer = &mut state.writer;
+    if let Some(clock) = clock {
+        handle_err2!(writer.write_timestamp(clock(), buf), count);
+        handle_err2!(writer.write_str(" ", buf), count);
+    }
+    handle_err2!(writer.write_str("[", buf), count);
+    handle_err2!(writer.write_str(record.level().as_str(), buf), count);
+    handle_err2!(writer.write_str(" ", buf), count);
+    handle_err2!(writer.write_str(record.target(), buf), count);
+    handle_err2!(writer.write_str("] ", buf), count);
+    handle_err2!(writer.write_args(*record.args(), buf), count);
+    handle_err1!(writer._writeln(buf), count);
+    Ok(count)
+}
+
+impl<const N: usize> Log for SerialLogger<N> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level_for(metadata.target())
+    }
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        critical_section::with(|cs| {
+            let mut state = self.state.borrow_ref_mut(cs);
+            let start = state.buf.len();
+            if write_record(&mut state, record, self.clock).is_err() {
+                // Never leave a partial record in the buffer.
+                state.buf.truncate(start);
+                state.dropped += 1;
+            }
+        });
+    }
+    fn flush(&self) {}
+}
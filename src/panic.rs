@@ -0,0 +1,173 @@
+//! Panic handler reporting over serial (requires the `panic-serial` feature).
+//!
+//! The handler outputs `panicked at <file>:<line>:<column>` and the panic message to the registered sink,
+//! keeps polling it so that the host can receive the text, and then halts or resets.
+//! Do not link another panic handler such as `panic_halt`.
+//!
+//! ```ignore
+//! let usb = cortex_m::singleton!(: UsbSerial<'static, UsbBus> = UsbSerial::new(usb_dev, serial)).unwrap();
+//! panic::register(usb, PanicAction::Halt);
+//!
+//! loop {
+//!     panic::with_sink(|usb| {
+//!         usb.poll();
+//!         let _ = writer.writeln_str("Hello, world!", usb);
+//!     });
+//! }
+//! ```
+
+use core::cell::Cell;
+use core::sync::atomic::{AtomicBool, Ordering};
+use critical_section::Mutex;
+use usb_device::class_prelude::{UsbBus, UsbError};
+
+use crate::{Sink, UsbSerial, Writer};
+
+/// Number of polls to wait for the host while the sink would block, before giving up the output.
+const MAX_POLLS: usize = 100_000;
+
+/// `Sink` which can be polled to make progress without other code running.
+pub trait PanicSink: Sink {
+    /// Give the underlying device a chance to send the written bytes.
+    fn poll(&mut self);
+}
+
+impl<B: UsbBus> PanicSink for UsbSerial<'_, B> {
+    fn poll(&mut self) {
+        UsbSerial::poll(self);
+    }
+}
+
+/// What the panic handler does after the report is output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PanicAction {
+    /// Keep polling the sink forever, so that the host can read the report at any time.
+    Halt,
+    /// Poll the sink `polls` times, then reset the chip.
+    Reset { polls: u32 },
+}
+
+#[derive(Clone, Copy)]
+struct Registered {
+    sink: *mut dyn PanicSink,
+    action: PanicAction,
+}
+
+// The pointer comes from a `&'static mut` given to `register`.
+unsafe impl Send for Registered {}
+
+static REGISTERED: Mutex<Cell<Option<Registered>>> = Mutex::new(Cell::new(None));
+static IN_USE: Mutex<Cell<bool>> = Mutex::new(Cell::new(false));
+static PANICKING: AtomicBool = AtomicBool::new(false);
+
+/// Register `sink` as the output of the panic handler.
+pub fn register(sink: &'static mut dyn PanicSink, action: PanicAction) {
+    let sink = sink as *mut dyn PanicSink;
+    critical_section::with(|cs| REGISTERED.borrow(cs).set(Some(Registered { sink, action })));
+}
+
+/// Call `f` with the registered sink, for output outside of panics.
+/// Returns `None` if no sink is registered, or if called inside another `with_sink`.
+pub fn with_sink<R>(f: impl FnOnce(&mut dyn PanicSink) -> R) -> Option<R> {
+    let registered = critical_section::with(|cs| {
+        if IN_USE.borrow(cs).replace(true) {
+            return None;
+        }
+        let registered = REGISTERED.borrow(cs).get();
+        if registered.is_none() {
+            IN_USE.borrow(cs).set(false);
+        }
+        registered
+    })?;
+    // `register` got exclusive access to the sink, and `IN_USE` prevents nested borrows.
+    let res = f(unsafe { &mut *registered.sink });
+    critical_section::with(|cs| IN_USE.borrow(cs).set(false));
+    Some(res)
+}
+
+/// Retries short writes and writes which would block, polling the sink in between,
+/// so that `Writer` gets the whole report through.
+struct Blocking<'a> {
+    sink: &'a mut dyn PanicSink,
+    gave_up: bool,
+}
+
+impl Sink for Blocking<'_> {
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        if self.gave_up {
+            return Err(UsbError::WouldBlock);
+        }
+        let mut count = 0;
+        for _ in 0..MAX_POLLS {
+            match self.sink.write(&data[count..]) {
+                Ok(n) => { count += n; }
+                Err(UsbError::WouldBlock) => {}
+                Err(e) => { return if count > 0 { Ok(count) } else { Err(e) }; }
+            }
+            if count == data.len() {
+                return Ok(count);
+            }
+            self.sink.poll();
+        }
+        // The host is not reading; do not wait for every remaining write.
+        self.gave_up = true;
+        if count > 0 { Ok(count) } else { Err(UsbError::WouldBlock) }
+    }
+}
+
+fn report(info: &core::panic::PanicInfo, serial: &mut dyn Sink) {
+    let mut writer = Writer::new();
+    let _ = writer.write_str("\r\npanicked at ", serial);
+    if let Some(location) = info.location() {
+        let _ = writer.write_str(location.file(), serial);
+        let _ = writer.write_str(":", serial);
+        let _ = writer.write_u32(location.line(), serial);
+        let _ = writer.write_str(":", serial);
+        let _ = writer.write_u32(location.column(), serial);
+    } else {
+        let _ = writer.write_str("unknown location", serial);
+    }
+    let _ = writer.writeln_str(":", serial);
+    let _ = writer.write_args(format_args!("{}", info.message()), serial);
+    let _ = writer._writeln(serial);
+}
+
+/// Output with `f` to the registered sink, then act as registered.
+/// Shared by the panic handler and other fatal error handlers.
+pub(crate) fn report_fatal(f: impl FnOnce(&mut dyn Sink)) -> ! {
+    cortex_m::interrupt::disable();
+    // A panic while reporting must not report again.
+    if PANICKING.load(Ordering::Relaxed) {
+        loop {
+            cortex_m::asm::nop();
+        }
+    }
+    PANICKING.store(true, Ordering::Relaxed);
+
+    let registered = critical_section::with(|cs| REGISTERED.borrow(cs).get());
+    let Some(Registered { sink, action }) = registered else {
+        loop {
+            cortex_m::asm::nop();
+        }
+    };
+    // The interrupted code never resumes, so its borrow of the sink is not used again.
+    let sink = unsafe { &mut *sink };
+    f(&mut Blocking { sink: &mut *sink, gave_up: false });
+    match action {
+        PanicAction::Halt => loop {
+            sink.poll();
+        },
+        PanicAction::Reset { polls } => {
+            for _ in 0..polls {
+                sink.poll();
+            }
+            cortex_m::peripheral::SCB::sys_reset();
+        }
+    }
+}
+
+#[cfg(not(test))]
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    report_fatal(|serial| report(info, serial))
+}
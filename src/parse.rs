@@ -0,0 +1,188 @@
+//! Parsers of numbers, the counterpart of the numeric writers of `Writer`.
+//!
+//! Every parser takes a `&str` or `&[u8]` (e.g. a line of `LineReader`), and ignores leading and trailing whitespace,
+//! so that the output of `write_f32_exp` (" 1.250e-03") or `write_u8_hex` ("0x1F") is read back as is.
+//!
+//! ```ignore
+//! let mut args = line.split_whitespace();
+//! if args.next() == Some("set_gain") {
+//!     let gain = parse::read_f32(args.next().unwrap_or(""))?;
+//! }
+//! let mut coeffs = [0f32; 4];
+//! // "[ 1.00, -2.50, ]" or "1 -2.5"
+//! let n = parse::read_f32_slice(line, &mut coeffs)?;
+//! ```
+
+use core::num::IntErrorKind;
+use core::str::FromStr;
+
+/// Errors of the parsers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input is empty or only whitespace.
+    Empty,
+    /// The input contains a character which is not part of the number.
+    InvalidDigit,
+    /// The number does not fit in the type.
+    Overflow,
+    /// The input has more elements than the slice.
+    TooMany,
+}
+
+fn to_str(input: &[u8]) -> Result<&str, ParseError> {
+    let s = core::str::from_utf8(input).map_err(|_| ParseError::InvalidDigit)?.trim();
+    if s.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    Ok(s)
+}
+
+fn int_error(e: core::num::ParseIntError) -> ParseError {
+    match e.kind() {
+        IntErrorKind::Empty => ParseError::Empty,
+        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => ParseError::Overflow,
+        _ => ParseError::InvalidDigit,
+    }
+}
+
+/// Remove the prefix (e.g. "0x" or "0X") of `s`.
+fn strip_prefix<'a>(s: &'a str, prefix: &str) -> &'a str {
+    if s.len() >= prefix.len() && s.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes()) {
+        &s[prefix.len()..]
+    } else {
+        s
+    }
+}
+
+macro_rules! read_int {
+    ($(#[$meta: meta])*
+    $type: ty, $name: ident) => {
+        $(#[$meta])*
+        pub fn $name(input: impl AsRef<[u8]>) -> Result<$type, ParseError> {
+            <$type>::from_str(to_str(input.as_ref())?).map_err(int_error)
+        }
+    };
+}
+macro_rules! read_radix {
+    ($(#[$meta: meta])*
+    $type: ty, $name: ident, $prefix: expr, $radix: expr) => {
+        $(#[$meta])*
+        pub fn $name(input: impl AsRef<[u8]>) -> Result<$type, ParseError> {
+            let s = strip_prefix(to_str(input.as_ref())?, $prefix);
+            if s.starts_with('+') || s.starts_with('-') {
+                return Err(ParseError::InvalidDigit);
+            }
+            <$type>::from_str_radix(s, $radix).map_err(int_error)
+        }
+    };
+}
+macro_rules! read_float {
+    ($(#[$meta: meta])*
+    $type: ty, $name: ident) => {
+        $(#[$meta])*
+        pub fn $name(input: impl AsRef<[u8]>) -> Result<$type, ParseError> {
+            let s = to_str(input.as_ref())?;
+            let val = <$type>::from_str(s).map_err(|_| ParseError::InvalidDigit)?;
+            if val.is_infinite() && !s.bytes().any(|b| b == b'i' || b == b'I') {
+                return Err(ParseError::Overflow);
+            }
+            Ok(val)
+        }
+    };
+}
+macro_rules! read_slice {
+    ($(#[$meta: meta])*
+    $type: ty, $name: ident, $f: ident) => {
+        $(#[$meta])*
+        pub fn $name(input: impl AsRef<[u8]>, out: &mut [$type]) -> Result<usize, ParseError> {
+            let mut count = 0;
+            for elem in split(input.as_ref()) {
+                if count >= out.len() {
+                    return Err(ParseError::TooMany);
+                }
+                out[count] = $f(elem)?;
+                count += 1;
+            }
+            Ok(count)
+        }
+    };
+}
+
+/// Split `input` into the elements of a slice, separated by whitespace or commas.
+/// Enclosing brackets, as output by `write_*_slice`, are removed.
+fn split(input: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut input = input.trim_ascii();
+    if let [b'[', inner @ .., b']'] = input {
+        input = inner;
+    }
+    input
+        .split(|&b| b == b',' || b.is_ascii_whitespace())
+        .filter(|elem| !elem.is_empty())
+}
+
+read_int!(/// Read an `i8`.
+    i8, read_i8);
+read_int!(/// Read an `i16`.
+    i16, read_i16);
+read_int!(/// Read an `i32`.
+    i32, read_i32);
+read_int!(/// Read an `i64`.
+    i64, read_i64);
+read_int!(/// Read an `isize`.
+    isize, read_isize);
+read_int!(/// Read a `u8`.
+    u8, read_u8);
+read_int!(/// Read a `u16`.
+    u16, read_u16);
+read_int!(/// Read a `u32`.
+    u32, read_u32);
+read_int!(/// Read a `u64`.
+    u64, read_u64);
+read_int!(/// Read a `usize`.
+    usize, read_usize);
+read_radix!(/// Read a hexadecimal `u8`, with or without "0x".
+    u8, read_u8_hex, "0x", 16);
+read_radix!(/// Read a hexadecimal `u16`, with or without "0x".
+    u16, read_u16_hex, "0x", 16);
+read_radix!(/// Read a hexadecimal `u32`, with or without "0x".
+    u32, read_u32_hex, "0x", 16);
+read_radix!(/// Read a hexadecimal `u64`, with or without "0x".
+    u64, read_u64_hex, "0x", 16);
+read_radix!(/// Read a hexadecimal `usize`, with or without "0x".
+    usize, read_usize_hex, "0x", 16);
+read_radix!(/// Read a binary `u8`, with or without "0b".
+    u8, read_u8_bin, "0b", 2);
+read_radix!(/// Read a binary `u16`, with or without "0b".
+    u16, read_u16_bin, "0b", 2);
+read_radix!(/// Read a binary `u32`, with or without "0b".
+    u32, read_u32_bin, "0b", 2);
+read_radix!(/// Read a binary `u64`, with or without "0b".
+    u64, read_u64_bin, "0b", 2);
+read_radix!(/// Read a binary `usize`, with or without "0b".
+    usize, read_usize_bin, "0b", 2);
+read_float!(/// Read an `f32` in decimal or exponential format, e.g. "-123.5" or "1.25e-3".
+    /// "inf" and "NaN" are accepted. A finite number too large for `f32` is `ParseError::Overflow`.
+    f32, read_f32);
+read_float!(/// Read an `f64` in decimal or exponential format, e.g. "-123.5" or "1.25e-3".
+    /// "inf" and "NaN" are accepted. A finite number too large for `f64` is `ParseError::Overflow`.
+    f64, read_f64);
+read_slice!(/// Read elements separated by whitespace or commas into `out`, and return the number of elements.
+    i8, read_i8_slice, read_i8);
+read_slice!(/// Read elements separated by whitespace or commas into `out`, and return the number of elements.
+    i16, read_i16_slice, read_i16);
+read_slice!(/// Read elements separated by whitespace or commas into `out`, and return the number of elements.
+    i32, read_i32_slice, read_i32);
+read_slice!(/// Read elements separated by whitespace or commas into `out`, and return the number of elements.
+    i64, read_i64_slice, read_i64);
+read_slice!(/// Read elements separated by whitespace or commas into `out`, and return the number of elements.
+    u8, read_u8_slice, read_u8);
+read_slice!(/// Read elements separated by whitespace or commas into `out`, and return the number of elements.
+    u16, read_u16_slice, read_u16);
+read_slice!(/// Read elements separated by whitespace or commas into `out`, and return the number of elements.
+    u32, read_u32_slice, read_u32);
+read_slice!(/// Read elements separated by whitespace or commas into `out`, and return the number of elements.
+    u64, read_u64_slice, read_u64);
+read_slice!(/// Read elements separated by whitespace or commas into `out`, and return the number of elements.
+    f32, read_f32_slice, read_f32);
+read_slice!(/// Read elements separated by whitespace or commas into `out`, and return the number of elements.
+    f64, read_f64_slice, read_f64);
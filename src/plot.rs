@@ -0,0 +1,76 @@
+//! Output for plotting tools: Arduino IDE Serial Plotter and Teleplot.
+//!
+//! ```ignore
+//! let mut plotter = Plotter::new();
+//! let channels = [Channel::new("target", 1.0, 1), Channel::new("actual", 0.987, 3)];
+//! // "target:1.0,actual:0.987\r\n"
+//! plotter.write_arduino(&channels, &mut serial)?;
+//! // ">target:1200:1.0\r\n>actual:1200:0.987\r\n"
+//! plotter.write_teleplot(1200, &channels, &mut serial)?;
+//! ```
+
+use usb_device::class_prelude::UsbError;
+
+use crate::{Sink, Writer};
+
+/// A labelled value, output to `nodp` decimal places.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Channel<'a> {
+    pub label: &'a str,
+    pub value: f32,
+    pub nodp: usize,
+}
+
+impl<'a> Channel<'a> {
+    /// Initialize Channel.
+    pub const fn new(label: &'a str, value: f32, nodp: usize) -> Self {
+        Self { label, value, nodp }
+    }
+}
+
+/// Output structure for plot lines.
+pub struct Plotter {
+    writer: Writer,
+}
+
+impl Plotter {
+    /// Initialize Plotter.
+    pub const fn new() -> Self {
+        Self { writer: Writer::new() }
+    }
+    /// Output `channels` as one line of the Arduino IDE Serial Plotter format, `label:value,label:value`.
+    pub fn write_arduino<S: Sink + ?Sized>(&mut self, channels: &[Channel], serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        for (i, channel) in channels.iter().enumerate() {
+            if i > 0 {
+                handle_err2!(self.writer.write_str(",", serial), count);
+            }
+            handle_err2!(self.writer.write_str(channel.label, serial), count);
+            handle_err2!(self.writer.write_str(":", serial), count);
+            handle_err2!(self.writer.write_f32(channel.value, channel.nodp, serial), count);
+        }
+        handle_err2!(self.writer.write_str("\r\n", serial), count);
+        Ok(count)
+    }
+    /// Output `channels` in the Teleplot format, one line of `>label:timestamp:value` per channel.
+    /// `timestamp` is in milliseconds.
+    pub fn write_teleplot<S: Sink + ?Sized>(&mut self, timestamp: u64, channels: &[Channel], serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        for channel in channels.iter() {
+            handle_err2!(self.writer.write_str(">", serial), count);
+            handle_err2!(self.writer.write_str(channel.label, serial), count);
+            handle_err2!(self.writer.write_str(":", serial), count);
+            handle_err2!(self.writer.write_u64(timestamp, serial), count);
+            handle_err2!(self.writer.write_str(":", serial), count);
+            handle_err2!(self.writer.write_f32(channel.value, channel.nodp, serial), count);
+            handle_err2!(self.writer.write_str("\r\n", serial), count);
+        }
+        Ok(count)
+    }
+}
+
+impl Default for Plotter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,268 @@
+//! Progress bars and spinners, redrawn in place with "\r".
+//!
+//! ```ignore
+//! let mut bar = ProgressBar::new(sectors * 4096).with_unit("B").with_clock(uptime_us);
+//! for sector in 0..sectors {
+//!     flash.erase(sector);
+//!     // "[#####     ]  52%  1.3 MB/s  ETA 0:04"
+//!     bar.set(u64::from(sector + 1) * 4096, &mut serial).ok();
+//! }
+//! bar.finish(&mut serial).ok();
+//!
+//! let mut spinner = Spinner::new();
+//! while !self_test_done() {
+//!     // "/ running self-test"
+//!     spinner.tick("running self-test", &mut serial).ok();
+//! }
+//! spinner.finish("self-test passed", &mut serial).ok();
+//! ```
+
+use usb_device::class_prelude::UsbError;
+
+use crate::{Sink, Writer};
+
+/// Sink counting the characters written through it, to erase the rest of a longer previous line.
+struct Columns<'a, S: Sink + ?Sized> {
+    serial: &'a mut S,
+    columns: usize,
+}
+
+impl<S: Sink + ?Sized> Sink for Columns<'_, S> {
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        let n = self.serial.write(data)?;
+        self.columns += data[..n].iter().filter(|&&b| b & 0xC0 != 0x80).count();
+        Ok(n)
+    }
+}
+
+/// Output spaces over the rest of the previous line of `prev` columns.
+fn erase_rest<S: Sink + ?Sized>(writer: &mut Writer, columns: usize, prev: usize, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+    let mut count = 0;
+    for _ in columns..prev {
+        handle_err2!(writer.write_str(" ", serial), count);
+    }
+    Ok(count)
+}
+
+/// Progress bar, e.g. `[#####     ] 52%  1.3 MB/s  ETA 0:04`.
+///
+/// The rate and ETA are shown if a clock is set.
+pub struct ProgressBar {
+    writer: Writer,
+    total: u64,
+    pos: u64,
+    width: usize,
+    fill: &'static str,
+    empty: &'static str,
+    unit: &'static str,
+    clock: Option<fn() -> u64>,
+    /// Time and position of the first update.
+    start: Option<(u64, u64)>,
+    /// Number of columns of the previous line.
+    drawn: usize,
+}
+
+impl ProgressBar {
+    /// Initialize ProgressBar for `total` units of work, with a bar of 20 columns of "#" and " ".
+    pub const fn new(total: u64) -> Self {
+        Self {
+            writer: Writer::new(),
+            total,
+            pos: 0,
+            width: 20,
+            fill: "#",
+            empty: " ",
+            unit: "",
+            clock: None,
+            start: None,
+            drawn: 0,
+        }
+    }
+    /// Set the number of columns of the bar.
+    pub const fn with_width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+    /// Set the characters of the done and remaining parts of the bar, e.g. "█" and "░".
+    pub const fn with_chars(mut self, fill: &'static str, empty: &'static str) -> Self {
+        self.fill = fill;
+        self.empty = empty;
+        self
+    }
+    /// Set the unit of the rate, e.g. "B" for "1.3 MB/s".
+    pub const fn with_unit(mut self, unit: &'static str) -> Self {
+        self.unit = unit;
+        self
+    }
+    /// Set the clock returning the time in microseconds, to show the rate and the ETA.
+    pub const fn with_clock(mut self, clock: fn() -> u64) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+    /// Current position.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+    /// Set the position to `pos` and redraw the bar.
+    pub fn set<S: Sink + ?Sized>(&mut self, pos: u64, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        self.pos = pos.min(self.total);
+        self.draw(serial)
+    }
+    /// Advance the position by `delta` and redraw the bar.
+    pub fn inc<S: Sink + ?Sized>(&mut self, delta: u64, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        self.set(self.pos.saturating_add(delta), serial)
+    }
+    /// Draw the bar at the end position, and break the line.
+    pub fn finish<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        handle_err2!(self.set(self.total, serial), count);
+        handle_err2!(self.writer.write_str("\r\n", serial), count);
+        self.drawn = 0;
+        Ok(count)
+    }
+    /// Redraw the bar.
+    pub fn draw<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        handle_err2!(self.writer.write_str("\r", serial), count);
+        let mut line = Columns { serial, columns: 0 };
+        handle_err2!(self.draw_line(&mut line), count);
+        let columns = line.columns;
+        handle_err2!(erase_rest(&mut self.writer, columns, self.drawn, serial), count);
+        self.drawn = columns;
+        Ok(count)
+    }
+    fn draw_line<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        let (filled, percent) = match self.total {
+            0 => (self.width, 100),
+            total => (
+                (self.pos as u128 * self.width as u128 / total as u128) as usize,
+                (self.pos as u128 * 100 / total as u128) as u64,
+            ),
+        };
+        handle_err2!(self.writer.write_str("[", serial), count);
+        for i in 0..self.width {
+            handle_err2!(self.writer.write_str(if i < filled { self.fill } else { self.empty }, serial), count);
+        }
+        handle_err2!(self.writer.write_str("] ", serial), count);
+        if percent < 100 {
+            handle_err2!(self.writer.write_str(" ", serial), count);
+        }
+        if percent < 10 {
+            handle_err2!(self.writer.write_str(" ", serial), count);
+        }
+        handle_err2!(self.writer.write_u64(percent, serial), count);
+        handle_err2!(self.writer.write_str("%", serial), count);
+        let now = match self.clock {
+            Some(clock) => clock(),
+            None => { return Ok(count); }
+        };
+        // Moving back, e.g. when a transfer restarts, measures the rate again from there.
+        if matches!(self.start, Some((_, start_pos)) if self.pos < start_pos) {
+            self.start = None;
+        }
+        let (start, start_pos) = *self.start.get_or_insert((now, self.pos));
+        let elapsed = now.saturating_sub(start);
+        if elapsed == 0 {
+            return Ok(count);
+        }
+        let rate = (self.pos - start_pos) as f32 * 1e6 / elapsed as f32;
+        handle_err2!(self.writer.write_str("  ", serial), count);
+        handle_err2!(write_rate(&mut self.writer, rate, self.unit, serial), count);
+        if self.pos < self.total && rate > 0.0 {
+            let eta = ((self.total - self.pos) as f32 / rate) as u64;
+            handle_err2!(self.writer.write_str("  ETA ", serial), count);
+            handle_err2!(write_duration(&mut self.writer, eta, serial), count);
+        }
+        Ok(count)
+    }
+}
+
+/// Output `rate` per second with an SI prefix, e.g. "1.3 MB/s".
+fn write_rate<S: Sink + ?Sized>(writer: &mut Writer, rate: f32, unit: &str, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+    let mut count = 0;
+    let (val, prefix) = if rate >= 1e9 {
+        (rate / 1e9, "G")
+    } else if rate >= 1e6 {
+        (rate / 1e6, "M")
+    } else if rate >= 1e3 {
+        (rate / 1e3, "k")
+    } else {
+        (rate, "")
+    };
+    handle_err2!(writer.write_f32(val, 1, serial), count);
+    handle_err2!(writer.write_str(" ", serial), count);
+    handle_err2!(writer.write_str(prefix, serial), count);
+    handle_err2!(writer.write_str(unit, serial), count);
+    handle_err2!(writer.write_str("/s", serial), count);
+    Ok(count)
+}
+
+/// Output `secs` as "m:ss", or "h:mm:ss" from an hour.
+fn write_duration<S: Sink + ?Sized>(writer: &mut Writer, secs: u64, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+    let mut count = 0;
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        handle_err2!(writer.write_u64(h, serial), count);
+        handle_err2!(writer.write_str(if m < 10 { ":0" } else { ":" }, serial), count);
+    }
+    handle_err2!(writer.write_u64(m, serial), count);
+    handle_err2!(writer.write_str(if s < 10 { ":0" } else { ":" }, serial), count);
+    handle_err2!(writer.write_u64(s, serial), count);
+    Ok(count)
+}
+
+/// Spinner followed by a message, e.g. `/ running self-test`.
+pub struct Spinner {
+    writer: Writer,
+    frames: &'static [&'static str],
+    frame: usize,
+    /// Number of columns of the previous line.
+    drawn: usize,
+}
+
+impl Spinner {
+    /// Initialize Spinner with the frames "|", "/", "-" and "\".
+    pub const fn new() -> Self {
+        Self { writer: Writer::new(), frames: &["|", "/", "-", "\\"], frame: 0, drawn: 0 }
+    }
+    /// Set the frames, e.g. `&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"]`.
+    pub const fn with_frames(mut self, frames: &'static [&'static str]) -> Self {
+        self.frames = frames;
+        self
+    }
+    fn draw<S: Sink + ?Sized>(&mut self, frame: &str, message: &str, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        handle_err2!(self.writer.write_str("\r", serial), count);
+        let mut line = Columns { serial, columns: 0 };
+        if !frame.is_empty() {
+            handle_err2!(self.writer.write_str(frame, &mut line), count);
+            handle_err2!(self.writer.write_str(" ", &mut line), count);
+        }
+        handle_err2!(self.writer.write_str(message, &mut line), count);
+        let columns = line.columns;
+        handle_err2!(erase_rest(&mut self.writer, columns, self.drawn, serial), count);
+        self.drawn = columns;
+        Ok(count)
+    }
+    /// Advance the spinner and redraw it with `message`.
+    pub fn tick<S: Sink + ?Sized>(&mut self, message: &str, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let frame = self.frames.get(self.frame).copied().unwrap_or("");
+        self.frame = if self.frame + 1 < self.frames.len() { self.frame + 1 } else { 0 };
+        self.draw(frame, message, serial)
+    }
+    /// Replace the spinner by `message`, and break the line.
+    pub fn finish<S: Sink + ?Sized>(&mut self, message: &str, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        handle_err2!(self.draw("", message, serial), count);
+        handle_err2!(self.writer.write_str("\r\n", serial), count);
+        self.drawn = 0;
+        Ok(count)
+    }
+}
+
+impl Default for Spinner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
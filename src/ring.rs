@@ -0,0 +1,90 @@
+use usb_device::class_prelude::UsbError;
+
+use crate::Sink;
+
+/// Fixed size byte queue which can be used as a `Sink`.
+///
+/// Bytes written to it are kept until `drain` sends them to another `Sink`,
+/// e.g. from the main loop after `usb_dev.poll`.
+pub struct RingBuffer<const N: usize> {
+    buf: [u8; N],
+    head: usize,
+    len: usize,
+}
+
+impl<const N: usize> RingBuffer<N> {
+    /// Initialize an empty RingBuffer.
+    pub const fn new() -> Self {
+        Self { buf: [0u8; N], head: 0, len: 0 }
+    }
+    /// Number of bytes waiting to be drained.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Returns `true` if there are no bytes waiting to be drained.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Number of bytes which can still be written.
+    pub fn free(&self) -> usize {
+        N - self.len
+    }
+    /// Discard all bytes.
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+    /// Discard the newest bytes so that `len` bytes remain.
+    /// Does nothing if `len` is not less than the current length.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            self.len = len;
+        }
+    }
+    /// Append as many bytes of `data` as fit, and return the number of bytes appended.
+    pub fn push(&mut self, data: &[u8]) -> usize {
+        let n = if data.len() < self.free() { data.len() } else { self.free() };
+        for (i, &b) in data[..n].iter().enumerate() {
+            self.buf[(self.head + self.len + i) % N] = b;
+        }
+        self.len += n;
+        n
+    }
+    /// Output the buffered bytes to `serial` until the buffer is empty or `serial` would block.
+    /// Returns the number of bytes output; bytes not accepted by `serial` stay in the buffer.
+    pub fn drain<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        let mut count = 0;
+        while self.len > 0 {
+            let end = if self.head + self.len > N { N } else { self.head + self.len };
+            match serial.write(&self.buf[self.head..end]) {
+                Ok(0) => { break; }
+                Ok(n) => {
+                    self.head = (self.head + n) % N;
+                    self.len -= n;
+                    count += n;
+                }
+                Err(UsbError::WouldBlock) => { break; }
+                Err(e) => { return Err((e, count)); }
+            }
+        }
+        if self.len == 0 {
+            self.head = 0;
+        }
+        Ok(count)
+    }
+}
+
+impl<const N: usize> Default for RingBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Sink for RingBuffer<N> {
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        match self.push(data) {
+            0 if !data.is_empty() => Err(UsbError::WouldBlock),
+            n => Ok(n),
+        }
+    }
+}
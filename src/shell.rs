@@ -0,0 +1,257 @@
+//! Command shell over a serial port, without allocation.
+//!
+//! ```ignore
+//! fn set_gain(args: &[&str], writer: &mut Writer, serial: &mut dyn Sink) -> Result<(), ShellError> {
+//!     let [gain] = args else { return Err(ShellError::BadArgs) };
+//!     let gain = parse::read_f32(gain)?;
+//!     writer.write_str("gain = ", serial)?;
+//!     writer.write_f32(gain, 3, serial)?;
+//!     writer.write_str("\r\n", serial)?;
+//!     Ok(())
+//! }
+//!
+//! static COMMANDS: [Command; 1] = [
+//!     Command { name: "set_gain", help: "<gain>  Set the gain", handler: set_gain },
+//! ];
+//!
+//! let mut shell = Shell::<64, 8>::new(&COMMANDS).with_prompt("imu> ");
+//! loop {
+//!     if usb_dev.poll(&mut [&mut serial]) {
+//!         shell.poll(&mut serial).ok();
+//!     }
+//! }
+//! ```
+
+use usb_device::class_prelude::UsbError;
+
+use crate::editor::Completions;
+use crate::input::{LineReader, ReadError};
+use crate::parse::ParseError;
+use crate::{Sink, Source, Writer};
+
+/// Errors of `Shell` and of command handlers.
+#[derive(Debug)]
+pub enum ShellError {
+    /// Error of the underlying sink or source.
+    Usb(UsbError),
+    /// No command has the name.
+    UnknownCommand,
+    /// The arguments are not valid for the command.
+    BadArgs,
+    /// The line has more arguments than `Shell` can hold.
+    TooManyArgs,
+    /// A quote was not closed.
+    UnterminatedQuote,
+    /// The line was longer than the buffer of `Shell`.
+    LineTooLong,
+    /// The line is not valid UTF-8.
+    Utf8,
+}
+
+impl From<UsbError> for ShellError {
+    fn from(e: UsbError) -> Self {
+        ShellError::Usb(e)
+    }
+}
+
+impl From<(UsbError, usize)> for ShellError {
+    fn from((e, _): (UsbError, usize)) -> Self {
+        ShellError::Usb(e)
+    }
+}
+
+impl From<ParseError> for ShellError {
+    fn from(_: ParseError) -> Self {
+        ShellError::BadArgs
+    }
+}
+
+impl From<ReadError> for ShellError {
+    fn from(e: ReadError) -> Self {
+        match e {
+            ReadError::Usb(e) => ShellError::Usb(e),
+            ReadError::Overflow => ShellError::LineTooLong,
+            ReadError::Utf8 => ShellError::Utf8,
+        }
+    }
+}
+
+/// Handler of a command. It gets the arguments after the name of the command.
+pub type Handler = fn(args: &[&str], writer: &mut Writer, serial: &mut dyn Sink) -> Result<(), ShellError>;
+
+/// An entry of the command table.
+#[derive(Clone, Copy)]
+pub struct Command {
+    pub name: &'static str,
+    /// Shown by `help`, e.g. "<gain>  Set the gain".
+    pub help: &'static str,
+    pub handler: Handler,
+}
+
+impl Completions for [Command] {
+    fn candidate(&self, index: usize) -> Option<&str> {
+        self.get(index).map(|c| c.name)
+    }
+}
+
+/// Split `line` into words separated by whitespace.
+///
+/// A word may be enclosed in double or single quotes to include whitespace; the quotes are removed.
+/// Returns the number of words stored in `words`.
+pub fn tokenize<'l>(line: &'l str, words: &mut [&'l str]) -> Result<usize, ShellError> {
+    let bytes = line.as_bytes();
+    let mut count = 0;
+    let mut i = 0;
+    loop {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i >= bytes.len() {
+            return Ok(count);
+        }
+        let word = if bytes[i] == b'"' || bytes[i] == b'\'' {
+            let quote = bytes[i];
+            let start = i + 1;
+            let end = match bytes[start..].iter().position(|&b| b == quote) {
+                Some(len) => start + len,
+                None => { return Err(ShellError::UnterminatedQuote); }
+            };
+            i = end + 1;
+            &line[start..end]
+        } else {
+            let start = i;
+            while i < bytes.len() && !bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            &line[start..i]
+        };
+        if count >= words.len() {
+            return Err(ShellError::TooManyArgs);
+        }
+        words[count] = word;
+        count += 1;
+    }
+}
+
+/// Shell reading lines of up to `N` bytes with up to `A` words (including the command name),
+/// and running the commands of the table.
+///
+/// `help` lists the commands, and `help <command>` shows the help of one.
+/// Errors are reported on the terminal before being returned.
+pub struct Shell<'a, const N: usize, const A: usize> {
+    commands: &'a [Command],
+    reader: LineReader<N>,
+    writer: Writer,
+    prompt: &'a str,
+    echo: bool,
+}
+
+impl<'a, const N: usize, const A: usize> Shell<'a, N, A> {
+    /// Initialize Shell with the command table, the prompt "> " and echo enabled.
+    pub const fn new(commands: &'a [Command]) -> Self {
+        Self { commands, reader: LineReader::new(), writer: Writer::new(), prompt: "> ", echo: true }
+    }
+    /// Set the prompt.
+    pub const fn with_prompt(mut self, prompt: &'a str) -> Self {
+        self.prompt = prompt;
+        self
+    }
+    /// Set whether the input is echoed back.
+    pub const fn with_echo(mut self, echo: bool) -> Self {
+        self.echo = echo;
+        self
+    }
+    /// `Writer` used for the output of the shell.
+    pub fn writer(&mut self) -> &mut Writer {
+        &mut self.writer
+    }
+    /// Output the prompt.
+    pub fn write_prompt<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (UsbError, usize)> {
+        self.writer.write_str(self.prompt, serial)
+    }
+    /// Read the available input and run the command if a line is completed.
+    /// The prompt is output again after the command.
+    pub fn poll<S: Source + Sink>(&mut self, serial: &mut S) -> Result<(), ShellError> {
+        let line = if self.echo {
+            self.reader.read_line_echo(&mut self.writer, serial)
+        } else {
+            self.reader.read_line(serial)
+        };
+        let res = match line {
+            Ok(None) => { return Ok(()); }
+            Ok(Some(line)) => run::<A>(self.commands, line, &mut self.writer, &mut *serial),
+            Err(ReadError::Usb(UsbError::WouldBlock)) => { return Ok(()); }
+            Err(e) => Err(e.into()),
+        };
+        let res = report(res, &mut self.writer, serial);
+        self.writer.write_str(self.prompt, serial)?;
+        res
+    }
+    /// Run the command of `line`, e.g. received by other means than `poll`.
+    pub fn execute<S: Sink>(&mut self, line: &str, serial: &mut S) -> Result<(), ShellError> {
+        let res = run::<A>(self.commands, line, &mut self.writer, &mut *serial);
+        report(res, &mut self.writer, serial)
+    }
+}
+
+/// Tokenize `line` into up to `A` words and run its command.
+fn run<const A: usize>(commands: &[Command], line: &str, writer: &mut Writer, serial: &mut dyn Sink) -> Result<(), ShellError> {
+    let mut words = [""; A];
+    let n = tokenize(line, &mut words)?;
+    let (name, args) = match words[..n].split_first() {
+        Some((&name, args)) => (name, args),
+        None => { return Ok(()); }
+    };
+    if name == "help" {
+        return help(commands, args, writer, serial);
+    }
+    match commands.iter().find(|c| c.name == name) {
+        Some(command) => (command.handler)(args, writer, serial),
+        None => Err(ShellError::UnknownCommand),
+    }
+}
+
+/// Output the help of `command`, with its name padded to `width`.
+fn write_help(command: &Command, width: usize, writer: &mut Writer, serial: &mut dyn Sink) -> Result<(), ShellError> {
+    writer.write_str("  ", serial)?;
+    writer.write_str(command.name, serial)?;
+    for _ in command.name.len()..width {
+        writer.write_str(" ", serial)?;
+    }
+    writer.write_str("  ", serial)?;
+    writer.write_str(command.help, serial)?;
+    writer.write_str("\r\n", serial)?;
+    Ok(())
+}
+
+fn help(commands: &[Command], args: &[&str], writer: &mut Writer, serial: &mut dyn Sink) -> Result<(), ShellError> {
+    match args {
+        [] => {
+            let width = commands.iter().map(|c| c.name.len()).max().unwrap_or(0);
+            for command in commands.iter() {
+                write_help(command, width, writer, serial)?;
+            }
+            Ok(())
+        }
+        [name] => match commands.iter().find(|c| c.name == *name) {
+            Some(command) => write_help(command, 0, writer, serial),
+            None => Err(ShellError::UnknownCommand),
+        },
+        _ => Err(ShellError::BadArgs),
+    }
+}
+
+/// Output the message of the error of `res`, and return `res`.
+fn report<S: Sink + ?Sized>(res: Result<(), ShellError>, writer: &mut Writer, serial: &mut S) -> Result<(), ShellError> {
+    let msg = match &res {
+        Ok(()) | Err(ShellError::Usb(_)) => { return res; }
+        Err(ShellError::UnknownCommand) => "unknown command; type \"help\" for the list\r\n",
+        Err(ShellError::BadArgs) => "invalid arguments; type \"help\" for the usage\r\n",
+        Err(ShellError::TooManyArgs) => "too many arguments\r\n",
+        Err(ShellError::UnterminatedQuote) => "unterminated quote\r\n",
+        Err(ShellError::LineTooLong) => "line too long\r\n",
+        Err(ShellError::Utf8) => "invalid UTF-8\r\n",
+    };
+    writer.write_str(msg, serial)?;
+    res
+}
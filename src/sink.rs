@@ -0,0 +1,200 @@
+use core::borrow::BorrowMut;
+use usb_device::class_prelude::{UsbBus, UsbError};
+use usb_device::device::UsbDevice;
+use usbd_serial::SerialPort;
+
+/// Destination of the bytes output by `Writer`.
+///
+/// `SerialPort` implements this trait, so `&mut serial` can be passed to every method of `Writer` as before.
+pub trait Sink {
+    /// Write bytes from `data` and return the number of bytes written.
+    ///
+    /// Like `SerialPort::write`, fewer bytes than `data.len()` may be written,
+    /// and `UsbError::WouldBlock` is returned when no bytes could be written.
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError>;
+}
+
+impl<B, RS, WS> Sink for SerialPort<'_, B, RS, WS>
+where
+    B: UsbBus,
+    RS: BorrowMut<[u8]>,
+    WS: BorrowMut<[u8]>,
+{
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        SerialPort::write(self, data)
+    }
+}
+
+impl<S: Sink + ?Sized> Sink for &mut S {
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        (**self).write(data)
+    }
+}
+
+/// Sink writing into a byte slice, e.g. to format a value before output.
+///
+/// Bytes which do not fit are not written, and `UsbError::WouldBlock` is returned when the slice is full.
+pub struct SliceSink<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> SliceSink<'a> {
+    /// Initialize SliceSink writing into `buf`.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+    /// Number of bytes written.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Whether no bytes have been written.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Forget the bytes written.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+    /// Bytes written.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+    /// Bytes written as `&str`, up to the last complete UTF-8 character.
+    pub fn as_str(&self) -> &str {
+        utf8_prefix(&self.buf[..self.len])
+    }
+    /// Bytes written as `&str` borrowing the slice, up to the last complete UTF-8 character.
+    pub fn into_str(self) -> &'a str {
+        utf8_prefix(&self.buf[..self.len])
+    }
+}
+
+/// `bytes` as `&str`, up to the last complete UTF-8 character.
+fn utf8_prefix(bytes: &[u8]) -> &str {
+    match core::str::from_utf8(bytes) {
+        Ok(s) => s,
+        // A character cut off at the end of the slice.
+        Err(e) => core::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or(""),
+    }
+}
+
+impl Sink for SliceSink<'_> {
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        let n = data.len().min(self.buf.len() - self.len);
+        if n == 0 && !data.is_empty() {
+            return Err(UsbError::WouldBlock);
+        }
+        self.buf[self.len..self.len + n].copy_from_slice(&data[..n]);
+        self.len += n;
+        Ok(n)
+    }
+}
+
+/// `heapless::Vec` as a sink, e.g. to check the output on the host.
+///
+/// Like `SliceSink`, bytes which do not fit are not written, and `UsbError::WouldBlock` is returned when the vector is full.
+#[cfg(feature = "heapless")]
+impl<const N: usize> Sink for heapless::Vec<u8, N> {
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        let n = data.len().min(N - self.len());
+        if n == 0 && !data.is_empty() {
+            return Err(UsbError::WouldBlock);
+        }
+        self.extend_from_slice(&data[..n]).ok();
+        Ok(n)
+    }
+}
+
+/// `heapless::String` as a sink, e.g. to keep formatted text.
+///
+/// Bytes which do not fit are not written, up to a complete UTF-8 character, and `UsbError::WouldBlock` is returned when the string is full.
+/// Bytes which are not UTF-8 are `UsbError::ParseError`.
+#[cfg(feature = "heapless")]
+impl<const N: usize> Sink for heapless::String<N> {
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        let n = data.len().min(N - self.len());
+        let (s, invalid) = match core::str::from_utf8(&data[..n]) {
+            Ok(s) => (s, false),
+            // Either a character cut off at the capacity, or bytes which are not UTF-8.
+            Err(e) => (utf8_prefix(&data[..n]), e.error_len().is_some()),
+        };
+        if s.is_empty() && !data.is_empty() {
+            return Err(if invalid { UsbError::ParseError } else { UsbError::WouldBlock });
+        }
+        self.push_str(s).ok();
+        Ok(s.len())
+    }
+}
+
+/// Source of the bytes read by `LineReader`.
+///
+/// `SerialPort` implements this trait, like `Sink`.
+pub trait Source {
+    /// Read bytes into `data` and return the number of bytes read.
+    ///
+    /// Like `SerialPort::read`, `UsbError::WouldBlock` is returned when no bytes are available.
+    fn read(&mut self, data: &mut [u8]) -> Result<usize, UsbError>;
+}
+
+impl<B, RS, WS> Source for SerialPort<'_, B, RS, WS>
+where
+    B: UsbBus,
+    RS: BorrowMut<[u8]>,
+    WS: BorrowMut<[u8]>,
+{
+    fn read(&mut self, data: &mut [u8]) -> Result<usize, UsbError> {
+        SerialPort::read(self, data)
+    }
+}
+
+impl<S: Source + ?Sized> Source for &mut S {
+    fn read(&mut self, data: &mut [u8]) -> Result<usize, UsbError> {
+        (**self).read(data)
+    }
+}
+
+/// `UsbDevice` and its `SerialPort` kept together, so that the device can be polled
+/// by code which only has access to the `Sink` (e.g. the panic handler).
+pub struct UsbSerial<'a, B: UsbBus> {
+    pub device: UsbDevice<'a, B>,
+    pub serial: SerialPort<'a, B>,
+}
+
+impl<'a, B: UsbBus> UsbSerial<'a, B> {
+    /// Initialize UsbSerial.
+    pub fn new(device: UsbDevice<'a, B>, serial: SerialPort<'a, B>) -> Self {
+        Self { device, serial }
+    }
+    /// Poll the device for the serial port.
+    /// Returns `true` if the serial port may have data to read.
+    pub fn poll(&mut self) -> bool {
+        self.device.poll(&mut [&mut self.serial])
+    }
+}
+
+impl<B: UsbBus> Sink for UsbSerial<'_, B> {
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        self.serial.write(data)
+    }
+}
+
+impl<B: UsbBus> Source for UsbSerial<'_, B> {
+    fn read(&mut self, data: &mut [u8]) -> Result<usize, UsbError> {
+        self.serial.read(data)
+    }
+}
+
+/// Write all of `data`, retrying short writes.
+/// Returns the error and the number of bytes written if `serial` fails or would block.
+pub(crate) fn write_all<S: Sink + ?Sized>(serial: &mut S, data: &[u8]) -> Result<usize, (UsbError, usize)> {
+    let mut count = 0;
+    while count < data.len() {
+        match serial.write(&data[count..]) {
+            Ok(0) => { return Err((UsbError::WouldBlock, count)); }
+            Ok(n) => { count += n; }
+            Err(e) => { return Err((e, count)); }
+        }
+    }
+    Ok(count)
+}
@@ -0,0 +1,318 @@
+//! Tables with aligned columns and borders.
+//!
+//! ```ignore
+//! static COLUMNS: [Column; 3] = [
+//!     Column::new("ch", 2).with_align(Align::Right),
+//!     Column::new("voltage", 8).with_align(Align::Right).with_nodp(3),
+//!     Column::new("result", 6).with_align(Align::Center),
+//! ];
+//! let mut table = TableWriter::new(&COLUMNS).with_border(Border::Unicode);
+//! table.write_header(&mut serial)?;
+//! for (ch, v) in voltages.iter().enumerate() {
+//!     table.cell_usize(ch, &mut serial)?;
+//!     table.cell_f32(*v, &mut serial)?;
+//!     table.cell_str(if (3.2..3.4).contains(v) { "PASS" } else { "FAIL" }, &mut serial)?;
+//!     table.end_row(&mut serial)?;
+//! }
+//! table.write_footer(&mut serial)?;
+//! // ┌────┬──────────┬────────┐
+//! // │ ch │  voltage │ result │
+//! // ├────┼──────────┼────────┤
+//! // │  0 │    3.300 │  PASS  │
+//! // │  1 │    3.123 │  FAIL  │
+//! // └────┴──────────┴────────┘
+//! ```
+
+use usb_device::class_prelude::UsbError;
+
+use crate::{SliceSink, Sink, Writer};
+
+/// Size of the buffer used to format numeric cells.
+const CELL: usize = 48;
+
+/// Shown instead of a number wider than its column.
+const HASHES: &str = "################################################";
+
+/// Errors of `TableWriter`.
+#[derive(Debug)]
+pub enum TableError {
+    /// Error of the underlying sink.
+    Usb(UsbError),
+    /// The row already has as many cells as columns. Nothing was output.
+    TooManyCells,
+}
+
+impl From<UsbError> for TableError {
+    fn from(e: UsbError) -> Self {
+        TableError::Usb(e)
+    }
+}
+
+/// Alignment of the cells of a column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+/// Style of the borders.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Border {
+    /// `+`, `-` and `|`.
+    Ascii,
+    /// Box-drawing characters, e.g. `┌`, `─` and `│`.
+    Unicode,
+    /// No borders. Cells are separated by two spaces.
+    None,
+}
+
+/// Characters of a border style: horizontal, vertical, and the corners and junctions
+/// of the top, middle and bottom lines, from left to right.
+struct Chars {
+    h: &'static str,
+    v: &'static str,
+    top: [&'static str; 3],
+    mid: [&'static str; 3],
+    bottom: [&'static str; 3],
+}
+
+const ASCII: Chars = Chars { h: "-", v: "|", top: ["+", "+", "+"], mid: ["+", "+", "+"], bottom: ["+", "+", "+"] };
+const UNICODE: Chars = Chars { h: "─", v: "│", top: ["┌", "┬", "┐"], mid: ["├", "┼", "┤"], bottom: ["└", "┴", "┘"] };
+
+/// A column of a table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Column<'a> {
+    pub header: &'a str,
+    /// Width of the cells in characters. Longer strings are cut off, and longer numbers are shown as `#`.
+    pub width: usize,
+    pub align: Align,
+    /// Decimal places of floating-point cells.
+    pub nodp: usize,
+}
+
+impl<'a> Column<'a> {
+    /// Initialize Column aligned to the left, with 2 decimal places.
+    pub const fn new(header: &'a str, width: usize) -> Self {
+        Self { header, width, align: Align::Left, nodp: 2 }
+    }
+    /// Set the alignment.
+    pub const fn with_align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+    /// Set the decimal places of floating-point cells.
+    pub const fn with_nodp(mut self, nodp: usize) -> Self {
+        self.nodp = nodp;
+        self
+    }
+}
+
+macro_rules! cell_int {
+    ($(#[$meta: meta])*
+    $int: ty, $name: ident, $f: ident) => {
+        $(#[$meta])*
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: $int, serial: &mut S) -> Result<usize, (TableError, usize)> {
+            let mut buf = [0u8; CELL];
+            let mut sink = SliceSink::new(&mut buf);
+            self.writer.$f(val, &mut sink).ok();
+            let len = sink.len();
+            self.cell_number(&buf[..len], serial)
+        }
+    };
+}
+macro_rules! cell_float {
+    ($(#[$meta: meta])*
+    $float: ty, $name: ident, $f: ident) => {
+        $(#[$meta])*
+        pub fn $name<S: Sink + ?Sized>(&mut self, val: $float, serial: &mut S) -> Result<usize, (TableError, usize)> {
+            let nodp = match self.columns.get(self.cell) {
+                Some(column) => column.nodp,
+                None => { return Err((TableError::TooManyCells, 0)); }
+            };
+            let mut buf = [0u8; CELL];
+            let mut sink = SliceSink::new(&mut buf);
+            self.writer.$f(val, nodp, &mut sink).ok();
+            let len = sink.len();
+            self.cell_number(&buf[..len], serial)
+        }
+    };
+}
+
+/// Writer of the rows of a table with the given columns.
+pub struct TableWriter<'a> {
+    writer: Writer,
+    columns: &'a [Column<'a>],
+    border: Border,
+    /// Number of cells output in the current row.
+    cell: usize,
+}
+
+impl<'a> TableWriter<'a> {
+    /// Initialize TableWriter with ASCII borders.
+    pub const fn new(columns: &'a [Column<'a>]) -> Self {
+        Self { writer: Writer::new(), columns, border: Border::Ascii, cell: 0 }
+    }
+    /// Set the style of the borders.
+    pub const fn with_border(mut self, border: Border) -> Self {
+        self.border = border;
+        self
+    }
+    fn chars(&self) -> Option<&'static Chars> {
+        match self.border {
+            Border::Ascii => Some(&ASCII),
+            Border::Unicode => Some(&UNICODE),
+            Border::None => None,
+        }
+    }
+    /// Output a horizontal line with the corners and junctions `ends`.
+    fn rule<S: Sink + ?Sized>(&mut self, ends: [&'static str; 3], h: &'static str, serial: &mut S) -> Result<usize, (TableError, usize)> {
+        let mut count = 0;
+        for (i, column) in self.columns.iter().enumerate() {
+            handle_err2!(self.writer.write_str(if i == 0 { ends[0] } else { ends[1] }, serial), count);
+            for _ in 0..column.width + 2 {
+                handle_err2!(self.writer.write_str(h, serial), count);
+            }
+        }
+        handle_err2!(self.writer.write_str(ends[2], serial), count);
+        handle_err2!(self.writer.write_str("\r\n", serial), count);
+        Ok(count)
+    }
+    /// Output the top border, the headers and the line under them.
+    pub fn write_header<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (TableError, usize)> {
+        let mut count = 0;
+        if let Some(chars) = self.chars() {
+            handle_err2!(self.rule(chars.top, chars.h, serial), count);
+        }
+        self.cell = 0;
+        for i in 0..self.columns.len() {
+            handle_err2!(self.cell_str(self.columns[i].header, serial), count);
+        }
+        handle_err2!(self.end_row(serial), count);
+        handle_err2!(self.write_separator(serial), count);
+        Ok(count)
+    }
+    /// Output a horizontal line between rows.
+    pub fn write_separator<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (TableError, usize)> {
+        match self.chars() {
+            Some(chars) => self.rule(chars.mid, chars.h, serial),
+            None => {
+                let mut count = 0;
+                for (i, column) in self.columns.iter().enumerate() {
+                    handle_err2!(self.writer.write_str(if i == 0 { " " } else { "  " }, serial), count);
+                    for _ in 0..column.width {
+                        handle_err2!(self.writer.write_str("-", serial), count);
+                    }
+                }
+                handle_err2!(self.writer.write_str("\r\n", serial), count);
+                Ok(count)
+            }
+        }
+    }
+    /// Output the bottom border.
+    pub fn write_footer<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (TableError, usize)> {
+        match self.chars() {
+            Some(chars) => self.rule(chars.bottom, chars.h, serial),
+            None => Ok(0),
+        }
+    }
+    /// Output the border before a cell, and `text` aligned in `width` columns.
+    fn cell<S: Sink + ?Sized>(&mut self, text: &str, chars: usize, serial: &mut S) -> Result<usize, (TableError, usize)> {
+        let column = match self.columns.get(self.cell) {
+            Some(column) => *column,
+            None => { return Err((TableError::TooManyCells, 0)); }
+        };
+        let mut count = 0;
+        let border = match (self.chars(), self.cell) {
+            (Some(chars), _) => chars.v,
+            (None, 0) => "",
+            (None, _) => " ",
+        };
+        handle_err2!(self.writer.write_str(border, serial), count);
+        handle_err2!(self.writer.write_str(" ", serial), count);
+        let pad = column.width - chars;
+        let left = match column.align {
+            Align::Left => 0,
+            Align::Right => pad,
+            Align::Center => pad / 2,
+        };
+        for _ in 0..left {
+            handle_err2!(self.writer.write_str(" ", serial), count);
+        }
+        handle_err2!(self.writer.write_str(text, serial), count);
+        for _ in left..pad {
+            handle_err2!(self.writer.write_str(" ", serial), count);
+        }
+        if self.border != Border::None {
+            handle_err2!(self.writer.write_str(" ", serial), count);
+        }
+        self.cell += 1;
+        Ok(count)
+    }
+    fn cell_number<S: Sink + ?Sized>(&mut self, text: &[u8], serial: &mut S) -> Result<usize, (TableError, usize)> {
+        let width = match self.columns.get(self.cell) {
+            Some(column) => column.width,
+            None => { return Err((TableError::TooManyCells, 0)); }
+        };
+        // Numbers are ASCII. A number filling the buffer may have been cut off.
+        let text = core::str::from_utf8(text).unwrap_or("").trim_start();
+        if text.len() <= width && text.len() < CELL {
+            return self.cell(text, text.len(), serial);
+        }
+        let hashes = &HASHES[..width.min(HASHES.len())];
+        self.cell(hashes, hashes.len(), serial)
+    }
+    /// Output a `&str` cell. It is cut off at the width of the column.
+    pub fn cell_str<S: Sink + ?Sized>(&mut self, val: &str, serial: &mut S) -> Result<usize, (TableError, usize)> {
+        let width = match self.columns.get(self.cell) {
+            Some(column) => column.width,
+            None => { return Err((TableError::TooManyCells, 0)); }
+        };
+        let (end, chars) = match val.char_indices().nth(width) {
+            Some((end, _)) => (end, width),
+            None => (val.len(), val.chars().count()),
+        };
+        self.cell(&val[..end], chars, serial)
+    }
+    /// Output an empty cell.
+    pub fn cell_empty<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (TableError, usize)> {
+        self.cell("", 0, serial)
+    }
+    cell_int!(/// Output an `i8` cell.
+        i8, cell_i8, write_i8);
+    cell_int!(/// Output an `i16` cell.
+        i16, cell_i16, write_i16);
+    cell_int!(/// Output an `i32` cell.
+        i32, cell_i32, write_i32);
+    cell_int!(/// Output an `i64` cell.
+        i64, cell_i64, write_i64);
+    cell_int!(/// Output an `isize` cell.
+        isize, cell_isize, write_isize);
+    cell_int!(/// Output a `u8` cell.
+        u8, cell_u8, write_u8);
+    cell_int!(/// Output a `u16` cell.
+        u16, cell_u16, write_u16);
+    cell_int!(/// Output a `u32` cell.
+        u32, cell_u32, write_u32);
+    cell_int!(/// Output a `u64` cell.
+        u64, cell_u64, write_u64);
+    cell_int!(/// Output a `usize` cell.
+        usize, cell_usize, write_usize);
+    cell_float!(/// Output an `f32` cell to the decimal places of the column.
+        f32, cell_f32, write_f32);
+    cell_float!(/// Output an `f64` cell to the decimal places of the column.
+        f64, cell_f64, write_f64);
+    /// End the current row. Missing cells are output empty.
+    pub fn end_row<S: Sink + ?Sized>(&mut self, serial: &mut S) -> Result<usize, (TableError, usize)> {
+        let mut count = 0;
+        while self.cell < self.columns.len() {
+            handle_err2!(self.cell_empty(serial), count);
+        }
+        if let Some(chars) = self.chars() {
+            handle_err2!(self.writer.write_str(chars.v, serial), count);
+        }
+        handle_err2!(self.writer.write_str("\r\n", serial), count);
+        self.cell = 0;
+        Ok(count)
+    }
+}
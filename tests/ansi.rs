@@ -0,0 +1,63 @@
+//! ANSI escape sequences, which are left out without the `ansi` feature.
+//!
+//! Run with `--no-default-features` to check that nothing is output then.
+
+mod common;
+
+use common::output;
+use serial_write::ansi::Color;
+
+#[cfg(feature = "ansi")]
+#[test]
+fn colors() {
+    assert_eq!(output(|w, s| w.write_fg(Color::Red, s)), "\x1b[31m");
+    assert_eq!(output(|w, s| w.write_fg(Color::White, s)), "\x1b[37m");
+    assert_eq!(output(|w, s| w.write_fg(Color::BrightBlack, s)), "\x1b[90m");
+    assert_eq!(output(|w, s| w.write_bg(Color::Black, s)), "\x1b[40m");
+    assert_eq!(output(|w, s| w.write_bg(Color::BrightCyan, s)), "\x1b[106m");
+    assert_eq!(output(|w, s| w.write_fg(Color::Indexed(208), s)), "\x1b[38;5;208m");
+    assert_eq!(output(|w, s| w.write_bg(Color::Rgb(255, 0, 128), s)), "\x1b[48;2;255;0;128m");
+}
+
+#[cfg(feature = "ansi")]
+#[test]
+fn attributes() {
+    assert_eq!(output(|w, s| w.write_bold(s)), "\x1b[1m");
+    assert_eq!(output(|w, s| w.write_underline(s)), "\x1b[4m");
+    assert_eq!(output(|w, s| w.write_reset(s)), "\x1b[0m");
+}
+
+#[cfg(feature = "ansi")]
+#[test]
+fn cursor() {
+    assert_eq!(output(|w, s| w.write_cursor_to(1, 1, s)), "\x1b[1;1H");
+    assert_eq!(output(|w, s| w.write_cursor_to(24, 80, s)), "\x1b[24;80H");
+    assert_eq!(output(|w, s| w.write_cursor_to(u16::MAX, 0, s)), "\x1b[65535;0H");
+    assert_eq!(output(|w, s| w.write_clear_screen(s)), "\x1b[2J\x1b[H");
+    assert_eq!(output(|w, s| w.write_clear_line(s)), "\x1b[2K\r");
+    assert_eq!(output(|w, s| w.write_clear_to_eol(s)), "\x1b[K");
+    assert_eq!(output(|w, s| w.write_save_cursor(s)), "\x1b7");
+    assert_eq!(output(|w, s| w.write_restore_cursor(s)), "\x1b8");
+    assert_eq!(output(|w, s| w.write_hide_cursor(s)), "\x1b[?25l");
+    assert_eq!(output(|w, s| w.write_show_cursor(s)), "\x1b[?25h");
+}
+
+#[cfg(not(feature = "ansi"))]
+#[test]
+fn disabled() {
+    assert_eq!(output(|w, s| w.write_fg(Color::Rgb(1, 2, 3), s)), "");
+    assert_eq!(output(|w, s| w.write_bg(Color::Red, s)), "");
+    assert_eq!(output(|w, s| w.write_bold(s)), "");
+    assert_eq!(output(|w, s| w.write_underline(s)), "");
+    assert_eq!(output(|w, s| w.write_reset(s)), "");
+    assert_eq!(output(|w, s| w.write_cursor_to(1, 1, s)), "");
+    assert_eq!(output(|w, s| w.write_clear_screen(s)), "");
+    assert_eq!(output(|w, s| w.write_clear_line(s)), "");
+    assert_eq!(output(|w, s| w.write_clear_to_eol(s)), "");
+    assert_eq!(output(|w, s| w.write_save_cursor(s)), "");
+    assert_eq!(output(|w, s| w.write_restore_cursor(s)), "");
+    assert_eq!(output(|w, s| w.write_hide_cursor(s)), "");
+    assert_eq!(output(|w, s| w.write_show_cursor(s)), "");
+    // The text around them is output as usual.
+    assert_eq!(output(|w, s| w.write_str("ok", s)), "ok");
+}
@@ -0,0 +1,96 @@
+//! CBOR encoding, checked against the examples of RFC 8949 Appendix A.
+
+mod common;
+
+use common::output_bytes;
+use serial_write::cbor::CborWriter;
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+}
+
+macro_rules! check {
+    ($f: ident, $($val: expr => $expected: expr),* $(,)?) => {
+        $(assert_eq!(output_bytes(|sink| CborWriter::new().$f($val, sink)), hex($expected), "{}", $expected);)*
+    };
+}
+
+#[test]
+fn unsigned_heads() {
+    check!(write_u64,
+        0 => "00", 23 => "17", 24 => "1818", 255 => "18ff", 256 => "190100", 65535 => "19ffff",
+        65536 => "1a00010000", 4294967295 => "1affffffff", 4294967296 => "1b0000000100000000",
+        u64::MAX => "1bffffffffffffffff",
+    );
+    check!(write_u8, 100 => "1864");
+    check!(write_u16, 1000 => "1903e8");
+    check!(write_u32, 1000000 => "1a000f4240");
+}
+
+#[test]
+fn negative_heads() {
+    check!(write_i64,
+        -1 => "20", -10 => "29", -24 => "37", -25 => "3818", -100 => "3863", -256 => "38ff",
+        -257 => "390100", -1000 => "3903e7", -65537 => "3a00010000", -4294967297 => "3b0000000100000000",
+        i64::MIN => "3b7fffffffffffffff", 10 => "0a",
+    );
+    check!(write_i8, i8::MIN => "387f");
+    check!(write_i16, i16::MIN => "397fff");
+    check!(write_i32, i32::MIN => "3a7fffffff");
+}
+
+#[test]
+fn floats_and_simple_values() {
+    check!(write_f32, 100000.0 => "fa47c35000", f32::MAX => "fa7f7fffff", f32::INFINITY => "fa7f800000");
+    check!(write_f64, 1.1 => "fb3ff199999999999a", -4.1 => "fbc010666666666666", 1.0e+300 => "fb7e37e43c8800759c");
+    check!(write_bool, false => "f4", true => "f5");
+    assert_eq!(output_bytes(|sink| CborWriter::new().write_null(sink)), hex("f6"));
+    assert_eq!(output_bytes(|sink| CborWriter::new().write_undefined(sink)), hex("f7"));
+}
+
+#[test]
+fn strings_and_containers() {
+    check!(write_str, "" => "60", "a" => "6161", "IETF" => "6449455446", "\"\\" => "62225c", "\u{00fc}" => "62c3bc");
+    check!(write_bytes, &[0u8; 0][..] => "40", &[1, 2, 3, 4][..] => "4401020304");
+    let long = "a".repeat(24);
+    assert_eq!(output_bytes(|sink| CborWriter::new().write_str(&long, sink))[..2], hex("7818"));
+    check!(begin_array, 0 => "80", 3 => "83", 25 => "9819");
+    check!(begin_map, 0 => "a0", 2 => "a2", 256 => "b90100");
+    check!(write_tag, 1 => "c1", 32 => "d820", 65536 => "da00010000");
+    check!(write_u32_slice, &[1, 2, 3][..] => "83010203");
+    check!(write_i16_slice, &[-1, 500][..] => "82201901f4");
+}
+
+#[test]
+fn indefinite_lengths() {
+    // [_ 1, [2, 3], [_ 4, 5]]
+    let data = output_bytes(|sink| {
+        let mut cbor = CborWriter::new();
+        let mut count = cbor.begin_array_indefinite(sink)?;
+        count += cbor.write_u8(1, sink)?;
+        count += cbor.write_u16_slice(&[2, 3], sink)?;
+        count += cbor.begin_array_indefinite(sink)?;
+        count += cbor.write_u8(4, sink)?;
+        count += cbor.write_u8(5, sink)?;
+        count += cbor.write_break(sink)?;
+        count += cbor.write_break(sink)?;
+        Ok(count)
+    });
+    assert_eq!(data, hex("9f018202039f0405ffff"));
+    // {_ "a": 1} and (_ "strea", "ming")
+    let data = output_bytes(|sink| {
+        let mut cbor = CborWriter::new();
+        let mut count = cbor.begin_map_indefinite(sink)?;
+        count += cbor.write_str("a", sink)?;
+        count += cbor.write_u8(1, sink)?;
+        count += cbor.write_break(sink)?;
+        count += cbor.begin_str_indefinite(sink)?;
+        count += cbor.write_str("strea", sink)?;
+        count += cbor.write_str("ming", sink)?;
+        count += cbor.write_break(sink)?;
+        count += cbor.begin_bytes_indefinite(sink)?;
+        count += cbor.write_break(sink)?;
+        Ok(count)
+    });
+    assert_eq!(data, hex("bf616101ff7f657374726561646d696e67ff5fff"));
+}
@@ -0,0 +1,31 @@
+//! Charts drawn with block characters.
+
+mod common;
+
+use common::output;
+use serial_write::chart::{Chart, Scale};
+
+#[test]
+fn bars() {
+    let mut chart = Chart::new().with_width(4);
+    let s = output(|_, sink| chart.write_bars_f32(&["x", "yaw"], &[1.0, 2.0], sink));
+    assert_eq!(s, "x   │██   1.00\r\nyaw │████ 2.00\r\n");
+    // Integer samples have no decimal places.
+    let s = output(|_, sink| chart.write_bars_i16(&["a", "b"], &[12, -4], sink));
+    assert_eq!(s, "a │████ 12\r\nb │     -4\r\n");
+}
+
+#[test]
+fn histogram_wide_bounds() {
+    let mut chart = Chart::new().with_width(2).with_scale(Scale::Fixed(-1e30, 1e30));
+    let s = output(|_, sink| chart.write_histogram_f32(&[-1e30, 0.0, 1.0], 2, sink));
+    // The bounds are not cut off, whatever their length.
+    assert_eq!(s, "-1000000015047466219876688855040.00 │█  1\r\n                               0.00 │██ 2\r\n");
+}
+
+#[test]
+fn sparkline() {
+    let mut chart = Chart::new();
+    let s = output(|_, sink| chart.write_sparkline_f32(&[0.0, 0.5, 1.0, f32::NAN], sink));
+    assert_eq!(s, "▁▅█ \r\n");
+}
@@ -0,0 +1,222 @@
+//! Mock sinks and USB bus for the host tests.
+
+#![allow(dead_code)]
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use serial_write::{Sink, Source, Writer};
+use usb_device::bus::PollResult;
+use usb_device::class_prelude::*;
+use usb_device::endpoint::EndpointType;
+use usb_device::UsbDirection;
+
+/// Output of `f` with a new `Writer`, checking that the returned count is the number of bytes written.
+pub fn output(f: impl FnOnce(&mut Writer, &mut MockSink) -> Result<usize, (UsbError, usize)>) -> String {
+    let mut writer = Writer::new();
+    let mut sink = MockSink::new();
+    let count = f(&mut writer, &mut sink).unwrap();
+    assert_eq!(count, sink.data.len());
+    sink.as_str().to_string()
+}
+
+/// Bytes written by `f`, e.g. for binary encoders, checking that the returned count is the number of bytes written.
+pub fn output_bytes(f: impl FnOnce(&mut MockSink) -> Result<usize, (UsbError, usize)>) -> Vec<u8> {
+    let mut sink = MockSink::new();
+    let count = f(&mut sink).unwrap();
+    assert_eq!(count, sink.data.len());
+    sink.data
+}
+
+/// Sink appending to a `Vec`, which can write fewer bytes than asked and block when full.
+pub struct MockSink {
+    pub data: Vec<u8>,
+    /// Maximum number of bytes written by each call of `write`.
+    chunk: usize,
+    /// Number of bytes after which `write` returns `UsbError::WouldBlock`.
+    capacity: usize,
+}
+
+impl MockSink {
+    pub fn new() -> Self {
+        Self { data: Vec::new(), chunk: usize::MAX, capacity: usize::MAX }
+    }
+    /// Write at most `chunk` bytes at a time.
+    pub fn with_chunk(mut self, chunk: usize) -> Self {
+        self.chunk = chunk;
+        self
+    }
+    /// Block after `capacity` bytes.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.data).unwrap()
+    }
+}
+
+impl Sink for MockSink {
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        let n = data.len().min(self.chunk).min(self.capacity - self.data.len());
+        if n == 0 && !data.is_empty() {
+            return Err(UsbError::WouldBlock);
+        }
+        self.data.extend_from_slice(&data[..n]);
+        Ok(n)
+    }
+}
+
+/// Source returning the bytes of `input`, at most `chunk` at a time.
+pub struct MockSource {
+    pub input: VecDeque<u8>,
+    chunk: usize,
+}
+
+impl MockSource {
+    pub fn new(input: &[u8]) -> Self {
+        Self { input: input.iter().copied().collect(), chunk: usize::MAX }
+    }
+    pub fn with_chunk(mut self, chunk: usize) -> Self {
+        self.chunk = chunk;
+        self
+    }
+}
+
+impl Source for MockSource {
+    fn read(&mut self, data: &mut [u8]) -> Result<usize, UsbError> {
+        let n = data.len().min(self.chunk).min(self.input.len());
+        if n == 0 {
+            return Err(UsbError::WouldBlock);
+        }
+        for b in data[..n].iter_mut() {
+            *b = self.input.pop_front().unwrap();
+        }
+        Ok(n)
+    }
+}
+
+/// Serial port of a terminal, reading from `source` and writing to `sink`, e.g. to check the echo of the input.
+pub struct MockSerial {
+    pub source: MockSource,
+    pub sink: MockSink,
+}
+
+impl MockSerial {
+    pub fn new(input: &[u8]) -> Self {
+        Self { source: MockSource::new(input), sink: MockSink::new() }
+    }
+    /// Bytes typed after the first ones.
+    pub fn type_bytes(&mut self, input: &[u8]) {
+        self.source.input.extend(input);
+    }
+}
+
+impl Source for MockSerial {
+    fn read(&mut self, data: &mut [u8]) -> Result<usize, UsbError> {
+        self.source.read(data)
+    }
+}
+
+impl Sink for MockSerial {
+    fn write(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        self.sink.write(data)
+    }
+}
+
+/// State of `MockBus`, shared with the test.
+#[derive(Default)]
+pub struct BusState {
+    /// Packets written to the bulk IN endpoint, i.e. sent to the host.
+    pub packets: Vec<Vec<u8>>,
+    /// Bytes to be read from the bulk OUT endpoint, i.e. sent by the host.
+    pub input: VecDeque<u8>,
+    /// Number of packets accepted before writes return `UsbError::WouldBlock`, e.g. `Some(0)`
+    /// for a host which is not reading.
+    pub packets_left: Option<usize>,
+    next_index: u8,
+    bulk_in: Option<EndpointAddress>,
+    bulk_out: Option<EndpointAddress>,
+}
+
+impl BusState {
+    /// Bytes sent to the host.
+    pub fn written(&self) -> Vec<u8> {
+        self.packets.concat()
+    }
+}
+
+/// `UsbBus` without hardware, for `SerialPort` on the host.
+pub struct MockBus {
+    state: Arc<Mutex<BusState>>,
+}
+
+impl MockBus {
+    pub fn new() -> (Self, Arc<Mutex<BusState>>) {
+        let state = Arc::new(Mutex::new(BusState::default()));
+        (Self { state: state.clone() }, state)
+    }
+}
+
+impl UsbBus for MockBus {
+    fn alloc_ep(
+        &mut self,
+        ep_dir: UsbDirection,
+        ep_addr: Option<EndpointAddress>,
+        ep_type: EndpointType,
+        _max_packet_size: u16,
+        _interval: u8,
+    ) -> usb_device::Result<EndpointAddress> {
+        let mut state = self.state.lock().unwrap();
+        let addr = match ep_addr {
+            Some(addr) => addr,
+            None => {
+                state.next_index += 1;
+                EndpointAddress::from_parts(state.next_index as usize, ep_dir)
+            }
+        };
+        if ep_type == EndpointType::Bulk {
+            match ep_dir {
+                UsbDirection::In => state.bulk_in = Some(addr),
+                UsbDirection::Out => state.bulk_out = Some(addr),
+            }
+        }
+        Ok(addr)
+    }
+    fn enable(&mut self) {}
+    fn reset(&self) {}
+    fn set_device_address(&self, _addr: u8) {}
+    fn write(&self, ep_addr: EndpointAddress, buf: &[u8]) -> usb_device::Result<usize> {
+        let mut state = self.state.lock().unwrap();
+        if Some(ep_addr) != state.bulk_in {
+            return Ok(buf.len());
+        }
+        match state.packets_left {
+            Some(0) => return Err(UsbError::WouldBlock),
+            Some(ref mut n) => *n -= 1,
+            None => {}
+        }
+        state.packets.push(buf.to_vec());
+        Ok(buf.len())
+    }
+    fn read(&self, ep_addr: EndpointAddress, buf: &mut [u8]) -> usb_device::Result<usize> {
+        let mut state = self.state.lock().unwrap();
+        if Some(ep_addr) != state.bulk_out || state.input.is_empty() {
+            return Err(UsbError::WouldBlock);
+        }
+        let n = buf.len().min(state.input.len());
+        for b in buf[..n].iter_mut() {
+            *b = state.input.pop_front().unwrap();
+        }
+        Ok(n)
+    }
+    fn set_stalled(&self, _ep_addr: EndpointAddress, _stalled: bool) {}
+    fn is_stalled(&self, _ep_addr: EndpointAddress) -> bool {
+        false
+    }
+    fn suspend(&self) {}
+    fn resume(&self) {}
+    fn poll(&self) -> PollResult {
+        PollResult::None
+    }
+}
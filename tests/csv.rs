@@ -0,0 +1,42 @@
+//! CSV and TSV rows.
+
+mod common;
+
+use common::MockSink;
+use serial_write::csv::{CsvError, CsvWriter};
+
+#[test]
+fn quoting() {
+    let mut csv = CsvWriter::new(3);
+    let mut sink = MockSink::new();
+    csv.write_header(&["time", "name", "temp"], &mut sink).unwrap();
+    csv.field_u32(1200, &mut sink).unwrap();
+    csv.field_str("sensor, \"left\"", &mut sink).unwrap();
+    csv.field_f32(23.456, 2, &mut sink).unwrap();
+    csv.end_row(&mut sink).unwrap();
+    assert_eq!(sink.as_str(), "time,name,temp\r\n1200,\"sensor, \"\"left\"\"\",23.46\r\n");
+
+    let mut tsv = CsvWriter::new(2).with_delimiter(b'\t').with_line_ending("\n");
+    let mut sink = MockSink::new();
+    tsv.field_str("a,b", &mut sink).unwrap();
+    tsv.field_str("c\td", &mut sink).unwrap();
+    tsv.end_row(&mut sink).unwrap();
+    assert_eq!(sink.as_str(), "a,b\t\"c\td\"\n");
+}
+
+#[test]
+fn field_count_errors() {
+    let mut csv = CsvWriter::new(2);
+    let mut sink = MockSink::new();
+    assert!(matches!(csv.write_header(&["a"], &mut sink), Err((CsvError::TooFewFields, 0))));
+    assert!(matches!(csv.write_header(&["a", "b", "c"], &mut sink), Err((CsvError::TooManyFields, 0))));
+    csv.field_i8(-1, &mut sink).unwrap();
+    csv.field_empty(&mut sink).unwrap();
+    assert!(matches!(csv.field_u8(3, &mut sink), Err((CsvError::TooManyFields, 0))));
+    csv.end_row(&mut sink).unwrap();
+    csv.field_i8(1, &mut sink).unwrap();
+    // The missing field is output empty.
+    assert!(matches!(csv.end_row(&mut sink), Err((CsvError::TooFewFields, 3))));
+    assert_eq!(csv.fields(), 0);
+    assert_eq!(sink.as_str(), "-1,\r\n1,\r\n");
+}
@@ -0,0 +1,61 @@
+//! Dashboard redrawn in place with ANSI sequences.
+
+#![cfg(feature = "ansi")]
+
+mod common;
+
+use common::output;
+use serial_write::dashboard::{Dashboard, DashboardError};
+
+#[test]
+fn full_redraw_then_changes() {
+    let mut dash = Dashboard::<2, 8>::new();
+    let temp = dash.add_field(1, 1, "temp: ").unwrap();
+    let state = dash.add_field(2, 3, "state: ").unwrap();
+    dash.set_f32(temp, 21.5, 1);
+    dash.set_str(state, "idle");
+    assert_eq!(output(|_, s| dash.draw(s)), "\x1b[2J\x1b[H\x1b[1;1Htemp: 21.5\x1b[2;3Hstate: idle");
+    assert_eq!(output(|_, s| dash.draw(s)), "");
+    // Only the value whose text has changed is drawn, after its label.
+    dash.set_f32(temp, 21.54, 1);
+    dash.set_str(state, "armed");
+    assert_eq!(output(|_, s| dash.draw(s)), "\x1b[2;10Harmed");
+    // The whole screen after `invalidate`.
+    dash.invalidate();
+    assert_eq!(output(|_, s| dash.draw(s)), "\x1b[2J\x1b[H\x1b[1;1Htemp: 21.5\x1b[2;3Hstate: armed");
+}
+
+#[test]
+fn shorter_value() {
+    let mut dash = Dashboard::<1, 8>::new();
+    let count = dash.add_field(3, 5, "n=").unwrap();
+    dash.set_u32(count, 1000);
+    assert_eq!(output(|_, s| dash.draw(s)), "\x1b[2J\x1b[H\x1b[3;5Hn=1000");
+    // The rest of the longer value is erased.
+    dash.set_i32(count, -7);
+    assert_eq!(output(|_, s| dash.draw(s)), "\x1b[3;7H-7  ");
+    dash.set_u64(count, 12);
+    assert_eq!(output(|_, s| dash.draw(s)), "\x1b[3;7H12");
+}
+
+#[test]
+fn long_values() {
+    let mut dash = Dashboard::<1, 4>::new();
+    let id = dash.add_field(1, 1, "").unwrap();
+    assert_eq!(dash.add_field(2, 1, ""), Err(DashboardError::Full));
+    // Cut off at a character boundary.
+    dash.set_str(id, "µµµ");
+    assert_eq!(output(|_, s| dash.draw(s)), "\x1b[2J\x1b[H\x1b[1;1Hµµ");
+    dash.set_i64(id, -123456);
+    assert_eq!(output(|_, s| dash.draw(s)), "\x1b[1;1H-123");
+}
+
+#[test]
+fn last_column() {
+    let mut dash = Dashboard::<1, 4>::new();
+    let id = dash.add_field(1, u16::MAX, "label: ").unwrap();
+    dash.draw(&mut common::MockSink::new()).unwrap();
+    // The column after the label saturates instead of overflowing.
+    dash.set_u32(id, 1);
+    assert_eq!(output(|_, s| dash.draw(s)), "\x1b[1;65535H1");
+}
@@ -0,0 +1,156 @@
+//! Sinks drawing on displays.
+
+use serial_write::display::{CharDisplay, CharLcd};
+use serial_write::{Sink, Writer};
+use usb_device::UsbError;
+
+/// Character display of 8 x 2 keeping its characters, and counting the moves of the cursor.
+struct MockLcd {
+    cells: [[char; 8]; 2],
+    cursor: (usize, usize),
+    moves: usize,
+    fail: bool,
+}
+
+impl MockLcd {
+    fn new() -> Self {
+        Self { cells: [[' '; 8]; 2], cursor: (0, 0), moves: 0, fail: false }
+    }
+    fn lines(&self) -> [String; 2] {
+        self.cells.map(|line| line.iter().collect())
+    }
+}
+
+impl CharDisplay for MockLcd {
+    type Error = ();
+    fn set_cursor(&mut self, col: u8, row: u8) -> Result<(), ()> {
+        self.cursor = (row as usize, col as usize);
+        self.moves += 1;
+        Ok(())
+    }
+    fn write_char(&mut self, c: char) -> Result<(), ()> {
+        if self.fail {
+            return Err(());
+        }
+        let (row, col) = self.cursor;
+        self.cells[row][col] = c;
+        // Like HD44780, the cursor runs off the row into memory which is not shown.
+        self.cursor = (row, col + 1);
+        Ok(())
+    }
+}
+
+fn lcd() -> CharLcd<MockLcd, 8, 2> {
+    CharLcd::new(MockLcd::new())
+}
+
+#[test]
+fn char_lcd_lines() {
+    let mut lcd = lcd();
+    let mut writer = Writer::new();
+    writer.write_str("T: ", &mut lcd).unwrap();
+    writer.writeln_f32(21.5, 1, &mut lcd).unwrap();
+    // The new line only starts at the next character.
+    assert_eq!(lcd.display().lines(), ["T: 21.5 ", "        "]);
+    writer.writeln_str("ok", &mut lcd).unwrap();
+    assert_eq!(lcd.display().lines(), ["T: 21.5 ", "ok      "]);
+    writer.writeln_str("next", &mut lcd).unwrap();
+    assert_eq!(lcd.display().lines(), ["ok      ", "next    "]);
+    // One move for each line, two for drawing the rows after the scroll, and one back to the last row.
+    assert_eq!(lcd.display().moves, 5);
+}
+
+#[test]
+fn char_lcd_wrap() {
+    let mut lcd = lcd();
+    // A full row does not leave an empty row before the next line.
+    lcd.write(b"12345678\r\nabcdefghij").unwrap();
+    assert_eq!(lcd.display().lines(), ["abcdefgh", "ij      "]);
+    lcd.write(b"\n\nx").unwrap();
+    assert_eq!(lcd.display().lines(), ["        ", "x       "]);
+}
+
+#[test]
+fn char_lcd_carriage_return() {
+    let mut lcd = lcd();
+    lcd.write(b"50%\r100%").unwrap();
+    assert_eq!(lcd.display().lines(), ["100%    ", "        "]);
+    lcd.clear().unwrap();
+    assert_eq!(lcd.display().lines(), ["        ", "        "]);
+    lcd.write(b"x").unwrap();
+    assert_eq!(lcd.display().lines(), ["x       ", "        "]);
+}
+
+#[test]
+fn char_lcd_utf8_and_escapes() {
+    let mut lcd = lcd();
+    // "°" cut between writes, and the colour of `ansi`.
+    lcd.write(b"21\xC2").unwrap();
+    lcd.write(b"\xB0C \x1b[1;31mhot\x1b[0m").unwrap();
+    assert_eq!(lcd.display().lines(), ["21°C hot", "        "]);
+    lcd.write(b"\n\xB0\xC2x").unwrap();
+    assert_eq!(lcd.display().lines(), ["21°C hot", "\u{FFFD}x      "]);
+}
+
+#[test]
+fn char_lcd_error() {
+    let mut lcd = lcd();
+    lcd.display_mut().fail = true;
+    assert!(matches!(lcd.write(b"ab"), Err(UsbError::InvalidState)));
+    lcd.display_mut().fail = false;
+    lcd.redraw().unwrap();
+    assert_eq!(lcd.into_inner().lines(), ["a       ", "        "]);
+}
+
+#[cfg(feature = "embedded-graphics")]
+mod graphics {
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::mono_font::ascii::FONT_6X10;
+    use embedded_graphics::mono_font::MonoTextStyleBuilder;
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::prelude::*;
+    use embedded_graphics::text::{Baseline, Text};
+    use serial_write::display::TextConsole;
+    use serial_write::Writer;
+
+    /// `lines` drawn with the style of the console, from `origin`.
+    fn expected(lines: &[&str], origin: Point) -> MockDisplay<BinaryColor> {
+        let style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+        let mut display = MockDisplay::new();
+        for (row, line) in lines.iter().enumerate() {
+            Text::with_baseline(line, origin + Point::new(0, 10 * row as i32), style, Baseline::Top).draw(&mut display).unwrap();
+        }
+        display
+    }
+
+    fn console() -> TextConsole<'static, MockDisplay<BinaryColor>, 6, 3> {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        TextConsole::new(display, &FONT_6X10, BinaryColor::On, BinaryColor::Off)
+    }
+
+    #[test]
+    fn text_console_scroll() {
+        let mut console = console();
+        let mut writer = Writer::new();
+        console.clear().unwrap();
+        writer.writeln_str("one", &mut console).unwrap();
+        writer.writeln_str("two", &mut console).unwrap();
+        writer.writeln_u32(123456789, &mut console).unwrap();
+        console.target().assert_eq(&expected(&["two   ", "123456", "789   "], Point::zero()));
+    }
+
+    #[test]
+    fn text_console_origin() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut console = TextConsole::<_, 6, 3>::new(display, &FONT_6X10, BinaryColor::On, BinaryColor::Off).with_origin(Point::new(2, 4));
+        console.redraw().unwrap();
+        Writer::new().write_str("a\r\nb", &mut console).unwrap();
+        console.into_inner().assert_eq(&expected(&["a     ", "b     ", "      "], Point::new(2, 4)));
+    }
+}
@@ -0,0 +1,128 @@
+//! Line editing with the key sequences of a terminal, checked against the exact output.
+
+mod common;
+
+use common::MockSerial;
+use serial_write::editor::LineEditor;
+use serial_write::Writer;
+
+const COMMANDS: [&str; 4] = ["help", "set_gain", "set_rate", "status"];
+
+/// Terminal typing into a `LineEditor`.
+struct Terminal<const N: usize, const H: usize> {
+    editor: LineEditor<'static, N, H>,
+    writer: Writer,
+    serial: MockSerial,
+}
+
+impl<const N: usize, const H: usize> Terminal<N, H> {
+    fn new() -> Self {
+        Self { editor: LineEditor::new(), writer: Writer::new(), serial: MockSerial::new(b"") }
+    }
+    /// Type `keys`, and return the output since the last call.
+    fn type_keys(&mut self, keys: &[u8]) -> String {
+        self.serial.type_bytes(keys);
+        let line = self.editor.read_line(&mut self.writer, &mut self.serial, &COMMANDS[..]).unwrap();
+        assert_eq!(line, None);
+        let out = self.serial.sink.as_str().to_string();
+        self.serial.sink.data.clear();
+        out
+    }
+    /// Press Enter, and return the line and the output.
+    fn enter(&mut self) -> (String, String) {
+        self.serial.type_bytes(b"\r");
+        let line = self.editor.read_line(&mut self.writer, &mut self.serial, &COMMANDS[..]).unwrap().unwrap().to_string();
+        let out = self.serial.sink.as_str().to_string();
+        self.serial.sink.data.clear();
+        (line, out)
+    }
+}
+
+const LEFT: &[u8] = b"\x1b[D";
+const RIGHT: &[u8] = b"\x1bOC";
+const HOME: &[u8] = b"\x1b[H";
+const END: &[u8] = b"\x1b[4~";
+const DELETE: &[u8] = b"\x1b[3~";
+const UP: &[u8] = b"\x1b[A";
+const DOWN: &[u8] = b"\x1bOB";
+
+#[test]
+fn cursor() {
+    let mut term = Terminal::<16, 0>::new();
+    assert_eq!(term.type_keys(b"abc"), "abc");
+    assert_eq!(term.type_keys(&[LEFT, LEFT].concat()), "\x1b[D\x1b[D");
+    // The rest of the line is drawn again after the insertion, and the cursor moved back.
+    assert_eq!(term.type_keys(b"X"), "Xbc\x1b[2D");
+    assert_eq!(term.type_keys(RIGHT), "\x1b[C");
+    assert_eq!(term.type_keys(HOME), "\x1b[3D");
+    assert_eq!(term.type_keys(LEFT), "");
+    assert_eq!(term.type_keys(END), "aXbc");
+    assert_eq!(term.type_keys(RIGHT), "");
+    // Ctrl-A and Ctrl-E.
+    assert_eq!(term.type_keys(b"\x01"), "\x1b[4D");
+    assert_eq!(term.type_keys(b"\x05"), "aXbc");
+    assert_eq!(term.enter(), ("aXbc".to_string(), "\r\n".to_string()));
+    // A character of several bytes is one column.
+    assert_eq!(term.type_keys("éa".as_bytes()), "éa");
+    assert_eq!(term.type_keys(&[LEFT, LEFT, b"x"].concat()), "\x1b[D\x1b[Dxéa\x1b[2D");
+    assert_eq!(term.enter().0, "xéa");
+}
+
+#[test]
+fn delete() {
+    let mut term = Terminal::<16, 0>::new();
+    term.type_keys(b"abcd\x01");
+    // The rest of the line is drawn again, and the last column erased.
+    assert_eq!(term.type_keys(DELETE), "bcd\x1b[K\x1b[3D");
+    // Ctrl-D.
+    assert_eq!(term.type_keys(b"\x04"), "cd\x1b[K\x1b[2D");
+    assert_eq!(term.type_keys(END), "cd");
+    assert_eq!(term.type_keys(DELETE), "");
+    assert_eq!(term.type_keys(b"\x7f"), "\x08\x1b[K");
+    assert_eq!(term.enter().0, "c");
+    // Ctrl-U.
+    term.type_keys(b"xy");
+    assert_eq!(term.type_keys(b"\x15"), "\x1b[2D\x1b[K");
+    assert_eq!(term.enter().0, "");
+}
+
+#[test]
+fn history() {
+    let mut term = Terminal::<16, 2>::new();
+    for line in [b"one".as_slice(), b"two", b"three", b"three", b""] {
+        term.type_keys(line);
+        term.enter();
+    }
+    // "one" is dropped, and a repeated or empty line is not kept.
+    assert_eq!((term.editor.history(0), term.editor.history(1), term.editor.history(2)), (Some("three"), Some("two"), None));
+    term.type_keys(b"dr");
+    assert_eq!(term.type_keys(UP), "\x1b[2Dthree\x1b[K");
+    assert_eq!(term.type_keys(UP), "\x1b[5Dtwo\x1b[K");
+    // Past the oldest line.
+    assert_eq!(term.type_keys(UP), "\x07");
+    assert_eq!(term.type_keys(DOWN), "\x1b[3Dthree\x1b[K");
+    // Back to the line being typed, then past the newest line.
+    assert_eq!(term.type_keys(DOWN), "\x1b[5Ddr\x1b[K");
+    assert_eq!(term.type_keys(DOWN), "\x07");
+    term.type_keys(UP);
+    assert_eq!(term.enter().0, "three");
+}
+
+#[test]
+fn completion() {
+    let mut term = Terminal::<16, 0>::new();
+    // A unique candidate is completed with a space.
+    assert_eq!(term.type_keys(b"he\t"), "help ");
+    assert_eq!(term.enter().0, "help ");
+    // Several candidates are completed up to their common prefix, then listed.
+    assert_eq!(term.type_keys(b"se\t"), "set_");
+    assert_eq!(term.type_keys(b"\t"), "\r\nset_gain  set_rate  \r\n> set_");
+    assert_eq!(term.type_keys(b"r\t"), "rate ");
+    assert_eq!(term.enter().0, "set_rate ");
+    assert_eq!(term.type_keys(b"s\t"), "s\r\nset_gain  set_rate  status  \r\n> s");
+    term.type_keys(b"\x15");
+    // No candidate, or the cursor not at the end of the first word.
+    assert_eq!(term.type_keys(b"x\t"), "x\x07");
+    assert_eq!(term.type_keys(&[b"\x7fs", LEFT, b"\t"].concat()), "\x08\x1b[Ks\x1b[D\x07");
+    assert_eq!(term.type_keys(&[END, b" \t"].concat()), "s \x07");
+}
@@ -0,0 +1,130 @@
+//! Comparison of the number formatting with `format!`, on random values.
+//!
+//! Each writer is compared in one of these modes:
+//!
+//! - `Mode::Exact`: the same as `format!`.
+//! - `Mode::Exp`: `{:.N e}`, except that non-negative values start with a space and the exponent
+//!   is padded to 3 characters, e.g. " 1.50e-03" and "-2.00e010" for "1.50e-3" and "-2.00e10".
+//!
+//! Slices are compared with `slice`, each element as in one of the modes, after "[ " and followed by ", ", then "]".
+
+mod common;
+
+use common::output;
+use proptest::prelude::*;
+
+#[derive(Clone, Copy)]
+enum Mode {
+    Exact,
+    Exp,
+}
+
+/// Output expected from that of `format!` in `mode`.
+fn expected(mode: Mode, std: String) -> String {
+    match mode {
+        Mode::Exact => std,
+        Mode::Exp => {
+            let sign = if std.starts_with('-') { "" } else { " " };
+            match std.split_once('e') {
+                Some((mantissa, exp)) => {
+                    let exp: i32 = exp.parse().unwrap();
+                    let exp = match exp < 0 {
+                        true => format!("-{:02}", -exp),
+                        false => format!("{:03}", exp),
+                    };
+                    format!("{}{}e{}", sign, mantissa, exp)
+                }
+                // "inf", "-inf" and "NaN".
+                None => format!("{}{}", sign, std),
+            }
+        }
+    }
+}
+
+fn slice(mode: Mode, elements: impl Iterator<Item = String>) -> String {
+    let mut s = "[ ".to_string();
+    for e in elements {
+        s += &expected(mode, e);
+        s += ", ";
+    }
+    s + "]"
+}
+
+macro_rules! int {
+    ($name: ident, $int: ty, $f: ident, $slice: ident) => {
+        proptest! {
+            #[test]
+            fn $name(val: $int, vals: Vec<$int>) {
+                prop_assert_eq!(output(|w, s| w.$f(val, s)), format!("{}", val));
+                prop_assert_eq!(output(|w, s| w.$slice(&vals, s)), slice(Mode::Exact, vals.iter().map(|v| v.to_string())));
+            }
+        }
+    };
+}
+int!(i8_, i8, write_i8, write_i8_slice);
+int!(i16_, i16, write_i16, write_i16_slice);
+int!(i32_, i32, write_i32, write_i32_slice);
+int!(i64_, i64, write_i64, write_i64_slice);
+int!(isize_, isize, write_isize, write_isize_slice);
+int!(u8_, u8, write_u8, write_u8_slice);
+int!(u16_, u16, write_u16, write_u16_slice);
+int!(u32_, u32, write_u32, write_u32_slice);
+int!(u64_, u64, write_u64, write_u64_slice);
+int!(usize_, usize, write_usize, write_usize_slice);
+
+macro_rules! hex {
+    ($name: ident, $int: ty, $f: ident) => {
+        proptest! {
+            #[test]
+            fn $name(val: $int) {
+                let digits = <$int>::BITS as usize / 4;
+                prop_assert_eq!(output(|w, s| w.$f(val, s)), format!("0x{:01$X}", val, digits));
+            }
+        }
+    };
+}
+hex!(u8_hex, u8, write_u8_hex);
+hex!(u16_hex, u16, write_u16_hex);
+hex!(u32_hex, u32, write_u32_hex);
+hex!(u64_hex, u64, write_u64_hex);
+hex!(usize_hex, usize, write_usize_hex);
+
+macro_rules! float {
+    ($name: ident, $float: ident, $max_nodp: expr, $f: ident, $exp: ident, $slice: ident, $slice_exp: ident) => {
+        proptest! {
+            #[test]
+            fn $name(val in proptest::num::$float::ANY, nodp in 0..=$max_nodp, vals in prop::collection::vec(proptest::num::$float::ANY, 0..4)) {
+                prop_assert_eq!(output(|w, s| w.$f(val, nodp, s)), format!("{:.*}", nodp, val));
+                prop_assert_eq!(output(|w, s| w.$exp(val, nodp, s)), expected(Mode::Exp, format!("{:.*e}", nodp, val)));
+                prop_assert_eq!(
+                    output(|w, s| w.$slice(&vals, nodp, s)),
+                    slice(Mode::Exact, vals.iter().map(|v| format!("{:.*}", nodp, v)))
+                );
+                prop_assert_eq!(
+                    output(|w, s| w.$slice_exp(&vals, nodp, s)),
+                    slice(Mode::Exp, vals.iter().map(|v| format!("{:.*e}", nodp, v)))
+                );
+            }
+        }
+    };
+}
+float!(f32_, f32, 12usize, write_f32, write_f32_exp, write_f32_slice, write_f32_slice_exp);
+float!(f64_, f64, 20usize, write_f64, write_f64_exp, write_f64_slice, write_f64_slice_exp);
+
+/// Values at which rounding is most likely to go wrong.
+#[test]
+fn float_edges() {
+    let vals = [
+        0.0, -0.0, 0.5, 1.5, 2.5, 0.125, 0.375, 9.5, 9.95, 9.995, 99.5, 999.9999, 1e-7, 1e21, 1e22, 3.3,
+        f64::MIN_POSITIVE, 5e-324, f64::MAX, f64::EPSILON, f64::INFINITY, f64::NEG_INFINITY, f64::NAN,
+    ];
+    for &val in vals.iter() {
+        for nodp in 0..=20 {
+            assert_eq!(output(|w, s| w.write_f64(val, nodp, s)), format!("{:.*}", nodp, val));
+            assert_eq!(output(|w, s| w.write_f64_exp(val, nodp, s)), expected(Mode::Exp, format!("{:.*e}", nodp, val)));
+            let val = val as f32;
+            assert_eq!(output(|w, s| w.write_f32(val, nodp, s)), format!("{:.*}", nodp, val));
+            assert_eq!(output(|w, s| w.write_f32_exp(val, nodp, s)), expected(Mode::Exp, format!("{:.*e}", nodp, val)));
+        }
+    }
+}
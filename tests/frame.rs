@@ -0,0 +1,96 @@
+//! COBS frames with checksums.
+
+mod common;
+
+use common::output_bytes;
+use serial_write::crc::{Crc16, Crc32};
+use serial_write::frame::{Checksum, FrameDecoder, FrameError, FrameWriter};
+
+/// Frame of message ID 7 with `payload`.
+fn encode(checksum: Checksum, payload: &[u8]) -> Vec<u8> {
+    let mut frame = FrameWriter::new(checksum);
+    output_bytes(|sink| {
+        let mut count = frame.begin(7, sink)?;
+        count += frame.write_bytes(payload, sink)?;
+        count += frame.end(sink)?;
+        Ok(count)
+    })
+}
+
+/// Results of feeding `data` to `decoder`, as the ID and payload of each frame.
+fn decode<const N: usize>(decoder: &mut FrameDecoder<N>, data: &[u8]) -> Vec<Result<(u8, Vec<u8>), FrameError>> {
+    data.iter()
+        .filter_map(|&b| decoder.feed(b).map(|res| res.map(|frame| (frame.id, frame.payload.to_vec()))))
+        .collect()
+}
+
+#[test]
+fn crc_check_values() {
+    assert_eq!(Crc16::checksum(b"123456789"), 0x906E);
+    assert_eq!(Crc32::checksum(b"123456789"), 0xCBF4_3926);
+    let mut crc = Crc16::new();
+    crc.update(b"1234");
+    crc.update(b"56789");
+    assert_eq!(crc.finish(), 0x906E);
+}
+
+#[test]
+fn round_trip() {
+    // Zeros, and runs longer than a COBS block.
+    let payloads: [Vec<u8>; 5] = [vec![], vec![0], vec![0, 0, 1, 0], (1..=255).collect(), (0..600).map(|i| (i % 256) as u8).collect()];
+    for checksum in [Checksum::Crc16, Checksum::Crc32] {
+        let mut decoder = FrameDecoder::<700>::new(checksum);
+        for payload in payloads.iter() {
+            let data = encode(checksum, payload);
+            assert_eq!(data.iter().position(|&b| b == 0), Some(data.len() - 1));
+            assert_eq!(decode(&mut decoder, &data), [Ok((7, payload.clone()))]);
+        }
+    }
+}
+
+#[test]
+fn payload_reader() {
+    let mut frame = FrameWriter::new(Checksum::Crc16);
+    let data = output_bytes(|sink| {
+        let mut count = frame.begin(1, sink)?;
+        count += frame.write_u32(1200, sink)?;
+        count += frame.write_f32_slice(&[1.5, -0.25], sink)?;
+        count += frame.write_i16(-2, sink)?;
+        count += frame.end(sink)?;
+        Ok(count)
+    });
+    let mut decoder = FrameDecoder::<32>::new(Checksum::Crc16);
+    let (last, rest) = data.split_last().unwrap();
+    assert!(rest.iter().all(|&b| decoder.feed(b).is_none()));
+    let frame = decoder.feed(*last).unwrap().unwrap();
+    assert_eq!(frame.id, 1);
+    let mut reader = frame.reader();
+    assert_eq!(reader.read_u32(), Some(1200));
+    assert_eq!(reader.read_f32(), Some(1.5));
+    assert_eq!(reader.read_f32(), Some(-0.25));
+    assert_eq!(reader.read_i16(), Some(-2));
+    assert_eq!(reader.read_u8(), None);
+}
+
+#[test]
+fn checksum_mismatch() {
+    for checksum in [Checksum::Crc16, Checksum::Crc32] {
+        let mut data = encode(checksum, b"abc");
+        // [code, 7, 'a', 'b', 'c', crc..., 0]
+        data[3] ^= 0x01;
+        let mut decoder = FrameDecoder::<16>::new(checksum);
+        assert_eq!(decode(&mut decoder, &data), [Err(FrameError::Checksum)]);
+        // The next frame is decoded.
+        assert_eq!(decode(&mut decoder, &encode(checksum, b"abc")), [Ok((7, b"abc".to_vec()))]);
+    }
+}
+
+#[test]
+fn broken_frames() {
+    let mut decoder = FrameDecoder::<4>::new(Checksum::Crc16);
+    assert_eq!(decode(&mut decoder, &[0x01, 0x00]), [Err(FrameError::TooShort)]);
+    // Cut off within a block.
+    assert_eq!(decode(&mut decoder, &[0x05, 0x07, 0x00]), [Err(FrameError::Encoding)]);
+    assert_eq!(decode(&mut decoder, &encode(Checksum::Crc16, b"abcdef")), [Err(FrameError::Overflow)]);
+    assert_eq!(decode(&mut decoder, &encode(Checksum::Crc16, b"a")), [Ok((7, b"a".to_vec()))]);
+}
@@ -0,0 +1,77 @@
+//! SLIP and HDLC framing.
+
+mod common;
+
+use common::MockSink;
+use serial_write::crc::Crc16;
+use serial_write::framing::{HdlcSink, SlipSink};
+use serial_write::{Sink, Writer};
+
+/// Contents of an HDLC frame between the flags, with the escapes removed.
+fn unescape(frame: &[u8]) -> Vec<u8> {
+    assert_eq!((frame[0], frame[frame.len() - 1]), (0x7E, 0x7E));
+    let mut out = Vec::new();
+    let mut escaped = false;
+    for &b in &frame[1..frame.len() - 1] {
+        assert_ne!(b, 0x7E);
+        match (escaped, b) {
+            (false, 0x7D) => { escaped = true; }
+            (true, b) => { out.push(b ^ 0x20); escaped = false; }
+            (false, b) => { out.push(b); }
+        }
+    }
+    out
+}
+
+#[test]
+fn slip_escaping() {
+    let mut slip = SlipSink::new(MockSink::new());
+    slip.write(&[0x01, 0xC0, 0xDB, 0x02]).unwrap();
+    slip.end_frame().unwrap();
+    assert_eq!(slip.into_inner().data, [0xC0, 0x01, 0xDB, 0xDC, 0xDB, 0xDD, 0x02, 0xC0]);
+}
+
+#[test]
+fn slip_short_writes() {
+    // Escape sequences split between writes are completed.
+    let mut slip = SlipSink::new(MockSink::new().with_chunk(1));
+    let mut writer = Writer::new();
+    let data = [0xC0, 0xDB, 0x41, 0xC0];
+    let mut count = 0;
+    while count < data.len() {
+        count += slip.write(&data[count..]).unwrap();
+    }
+    slip.end_frame().unwrap();
+    writer.write_str("ok", &mut slip).unwrap();
+    assert_eq!(slip.into_inner().data, [0xC0, 0xDB, 0xDC, 0xDB, 0xDD, 0x41, 0xDB, 0xDC, 0xC0, 0xC0, b'o']);
+}
+
+#[test]
+fn hdlc_fcs() {
+    let mut hdlc = HdlcSink::new(MockSink::new());
+    let mut writer = Writer::new();
+    writer.write_str("temp=", &mut hdlc).unwrap();
+    writer.write_f32(23.456, 2, &mut hdlc).unwrap();
+    hdlc.end_frame().unwrap();
+    let contents = unescape(&hdlc.into_inner().data);
+    let (text, fcs) = contents.split_at(contents.len() - 2);
+    assert_eq!(text, b"temp=23.46");
+    assert_eq!(fcs, Crc16::checksum(b"temp=23.46").to_le_bytes());
+    // The FCS over the contents and the FCS is the constant of RFC 1662 (0xF0B8 before the final XOR).
+    assert_eq!(Crc16::checksum(&contents), !0xF0B8);
+}
+
+#[test]
+fn hdlc_escaping() {
+    let mut hdlc = HdlcSink::new(MockSink::new()).with_accm(1 << 0x11);
+    hdlc.write(&[0x7E, 0x7D, 0x11, 0x13]).unwrap();
+    hdlc.end_frame().unwrap();
+    let data = hdlc.into_inner().data;
+    // The control characters not in the ACCM are not escaped.
+    assert_eq!(data[..8], [0x7E, 0x7D, 0x5E, 0x7D, 0x5D, 0x7D, 0x31, 0x13]);
+    assert_eq!(unescape(&data)[..4], [0x7E, 0x7D, 0x11, 0x13]);
+    // An empty frame has only the FCS.
+    let mut hdlc = HdlcSink::new(MockSink::new());
+    hdlc.end_frame().unwrap();
+    assert_eq!(unescape(&hdlc.into_inner().data), Crc16::checksum(&[]).to_le_bytes());
+}
@@ -0,0 +1,81 @@
+//! Line input, with and without echo.
+
+mod common;
+
+use common::{MockSerial, MockSource};
+use serial_write::input::{LineReader, ReadError};
+use serial_write::Writer;
+
+/// Lines completed by the available input.
+fn read_lines<const N: usize>(reader: &mut LineReader<N>, src: &mut MockSource) -> Vec<String> {
+    let mut lines = Vec::new();
+    while let Some(line) = reader.read_line(src).unwrap() {
+        lines.push(line.to_string());
+    }
+    lines
+}
+
+#[test]
+fn line_endings() {
+    let mut reader = LineReader::<16>::new();
+    let mut src = MockSource::new(b"a\rb\nc\r\nd\n\re");
+    // LF then CR are two line endings.
+    assert_eq!(read_lines(&mut reader, &mut src), ["a", "b", "c", "d", ""]);
+    assert_eq!(reader.pending(), b"e");
+    // The LF of CRLF is skipped when it comes in the next read.
+    src.input.extend(b"\r");
+    assert_eq!(read_lines(&mut reader, &mut src), ["e"]);
+    src.input.extend(b"\nf\n");
+    assert_eq!(read_lines(&mut reader, &mut src), ["f"]);
+}
+
+#[test]
+fn backspace() {
+    let mut reader = LineReader::<16>::new();
+    // Nothing to erase at the start of the line.
+    let mut src = MockSource::new(b"\x08\x7fab\x08\x08\x08c\x1b\n");
+    assert_eq!(read_lines(&mut reader, &mut src), ["c"]);
+    // A character of several bytes is erased at once.
+    let mut src = MockSource::new("xé\x7f\ty\r".as_bytes());
+    assert_eq!(read_lines(&mut reader, &mut src), ["x\ty"]);
+}
+
+#[test]
+fn overflow() {
+    let mut reader = LineReader::<4>::new();
+    let mut src = MockSource::new(b"abcd\nabcde\x7f\nok\n");
+    assert_eq!(reader.read_line(&mut src).unwrap(), Some("abcd"));
+    // The whole line is discarded, even if it is shortened again.
+    assert!(matches!(reader.read_line(&mut src), Err(ReadError::Overflow)));
+    assert_eq!(reader.read_line(&mut src).unwrap(), Some("ok"));
+    let mut src = MockSource::new(b"\xff\xfe\n");
+    assert!(matches!(reader.read_line(&mut src), Err(ReadError::Utf8)));
+}
+
+#[test]
+fn echo() {
+    let mut reader = LineReader::<4>::new();
+    let mut writer = Writer::new();
+    let mut serial = MockSerial::new(b"\x7fab\x7fc\x1b\r\n");
+    assert_eq!(reader.read_line_echo(&mut writer, &mut serial).unwrap(), Some("ac"));
+    assert_eq!(serial.sink.as_str(), "ab\x08 \x08c\r\n");
+    // "é" is echoed once complete.
+    serial.sink.data.clear();
+    serial.type_bytes(b"\xc3");
+    assert_eq!(reader.read_line_echo(&mut writer, &mut serial).unwrap(), None);
+    assert_eq!(serial.sink.as_str(), "");
+    serial.type_bytes(b"\xa9xyz");
+    assert_eq!(reader.read_line_echo(&mut writer, &mut serial).unwrap(), None);
+    assert_eq!(serial.sink.as_str(), "éxy");
+    serial.type_bytes(b"\n");
+    assert!(matches!(reader.read_line_echo(&mut writer, &mut serial), Err(ReadError::Overflow)));
+    assert_eq!(serial.sink.as_str(), "éxy\r\n");
+}
+
+#[test]
+fn no_echo() {
+    let mut reader = LineReader::<8>::new();
+    let mut serial = MockSerial::new(b"ab\x7fc\r\n");
+    assert_eq!(reader.read_line(&mut serial).unwrap(), Some("ac"));
+    assert!(serial.sink.data.is_empty());
+}
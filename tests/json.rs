@@ -0,0 +1,94 @@
+//! Streaming JSON output.
+
+mod common;
+
+use common::MockSink;
+use serial_write::json::{JsonError, JsonWriter};
+
+#[test]
+fn nested_values() {
+    let mut json = JsonWriter::<4>::new();
+    let mut sink = MockSink::new();
+    let s = &mut sink;
+    json.begin_object(s).unwrap();
+    json.key("temp", s).unwrap();
+    json.value_f32(23.456, 2, s).unwrap();
+    json.key("acc", s).unwrap();
+    json.begin_array(s).unwrap();
+    json.value_i16(-1, s).unwrap();
+    json.value_f32(f32::NAN, 2, s).unwrap();
+    json.begin_object(s).unwrap();
+    json.end_object(s).unwrap();
+    json.end_array(s).unwrap();
+    json.key("ok", s).unwrap();
+    json.value_bool(true, s).unwrap();
+    json.key("none", s).unwrap();
+    json.value_null(s).unwrap();
+    assert_eq!(json.depth(), 1);
+    json.end_object(s).unwrap();
+    json.end_line(s).unwrap();
+    json.value_u64(u64::MAX, s).unwrap();
+    json.end_line(s).unwrap();
+    assert_eq!(
+        sink.as_str(),
+        "{\"temp\":23.46,\"acc\":[-1,null,{}],\"ok\":true,\"none\":null}\r\n18446744073709551615\r\n"
+    );
+}
+
+#[test]
+fn escaping() {
+    let mut json = JsonWriter::<1>::new();
+    let mut sink = MockSink::new();
+    json.begin_object(&mut sink).unwrap();
+    json.key("a\"b", &mut sink).unwrap();
+    json.value_str("\\ \n\r\t\x08\x0C\x01\x1F\x7F é/", &mut sink).unwrap();
+    json.end_object(&mut sink).unwrap();
+    // DEL and non-ASCII characters need no escape.
+    assert_eq!(sink.as_str(), concat!(r#"{"a\"b":"\\ \n\r\t\b\f\u0001\u001f"#, "\x7F", r#" é/"}"#));
+}
+
+macro_rules! misuse {
+    ($json: ident, $sink: ident, $call: expr, $err: pat) => {
+        let len = $sink.data.len();
+        assert!(matches!($call, Err(($err, 0))));
+        // Nothing is output.
+        assert_eq!($sink.data.len(), len);
+    };
+}
+
+#[test]
+fn misuse_errors() {
+    let mut json = JsonWriter::<2>::new();
+    let mut sink = MockSink::new();
+    misuse!(json, sink, json.key("k", &mut sink), JsonError::ValueExpected);
+    misuse!(json, sink, json.end_object(&mut sink), JsonError::Mismatched);
+    misuse!(json, sink, json.end_line(&mut sink), JsonError::Incomplete);
+    json.begin_object(&mut sink).unwrap();
+    misuse!(json, sink, json.value_u8(1, &mut sink), JsonError::KeyExpected);
+    misuse!(json, sink, json.end_array(&mut sink), JsonError::Mismatched);
+    misuse!(json, sink, json.end_line(&mut sink), JsonError::Incomplete);
+    json.key("k", &mut sink).unwrap();
+    misuse!(json, sink, json.key("k", &mut sink), JsonError::ValueExpected);
+    misuse!(json, sink, json.end_object(&mut sink), JsonError::ValueExpected);
+    json.begin_array(&mut sink).unwrap();
+    misuse!(json, sink, json.begin_array(&mut sink), JsonError::TooDeep);
+    misuse!(json, sink, json.key("k", &mut sink), JsonError::ValueExpected);
+    json.end_array(&mut sink).unwrap();
+    json.end_object(&mut sink).unwrap();
+    misuse!(json, sink, json.value_str("x", &mut sink), JsonError::Complete);
+    assert_eq!(sink.as_str(), "{\"k\":[]}");
+    // A new line after `reset`.
+    json.reset();
+    json.value_i32(-5, &mut sink).unwrap();
+    json.end_line(&mut sink).unwrap();
+    assert_eq!(sink.as_str(), "{\"k\":[]}-5\r\n");
+}
+
+#[test]
+fn sink_errors() {
+    let mut json = JsonWriter::<2>::new();
+    let mut sink = MockSink::new().with_capacity(3);
+    json.begin_array(&mut sink).unwrap();
+    json.value_u8(10, &mut sink).unwrap();
+    assert!(matches!(json.value_u8(20, &mut sink), Err((JsonError::Usb(_), _))));
+}
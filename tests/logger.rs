@@ -0,0 +1,80 @@
+//! `log` backend buffering the formatted records.
+
+#![cfg(feature = "log")]
+
+mod common;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use common::MockSink;
+use log::{Level, LevelFilter, Log, Record};
+use serial_write::logger::SerialLogger;
+
+/// Critical sections of the host, as a lock shared by the test threads.
+struct HostCriticalSection;
+critical_section::set_impl!(HostCriticalSection);
+
+static LOCKED: AtomicBool = AtomicBool::new(false);
+
+unsafe impl critical_section::Impl for HostCriticalSection {
+    unsafe fn acquire() -> critical_section::RawRestoreState {
+        while LOCKED.swap(true, Ordering::Acquire) {
+            std::hint::spin_loop();
+        }
+        Default::default()
+    }
+    unsafe fn release(_: critical_section::RawRestoreState) {
+        LOCKED.store(false, Ordering::Release);
+    }
+}
+
+fn log<const N: usize>(logger: &SerialLogger<N>, level: Level, target: &str, message: &str) {
+    logger.log(&Record::builder().level(level).target(target).args(format_args!("{}", message)).build());
+}
+
+/// Records buffered so far.
+fn drain<const N: usize>(logger: &SerialLogger<N>) -> String {
+    let mut sink = MockSink::new();
+    logger.drain(&mut sink).unwrap();
+    sink.as_str().to_string()
+}
+
+#[test]
+fn filters() {
+    let logger = SerialLogger::<256>::new()
+        .with_max_level(LevelFilter::Info)
+        .with_targets(&[("usb_device", LevelFilter::Warn), ("app::imu", LevelFilter::Trace)]);
+    log(&logger, Level::Info, "app", "a");
+    log(&logger, Level::Debug, "app", "b");
+    log(&logger, Level::Info, "usb_device", "c");
+    log(&logger, Level::Warn, "usb_device::bus", "d");
+    log(&logger, Level::Trace, "app::imu::spi", "e");
+    // Not a module below "app::imu".
+    log(&logger, Level::Debug, "app::imux", "f");
+    assert_eq!(drain(&logger), "[INFO app] a\r\n[WARN usb_device::bus] d\r\n[TRACE app::imu::spi] e\r\n");
+    assert_eq!(logger.dropped(), 0);
+}
+
+#[test]
+fn timestamp() {
+    let logger = SerialLogger::<64>::new().with_clock(|| 12_345_678);
+    log(&logger, Level::Error, "app", "x = 1");
+    assert_eq!(drain(&logger), "[   12.345678] [ERROR app] x = 1\r\n");
+}
+
+#[test]
+fn long_records() {
+    let logger = SerialLogger::<32>::new();
+    // 18 bytes, then a record which does not fit in the rest.
+    log(&logger, Level::Info, "app", "first");
+    log(&logger, Level::Info, "app", "second record");
+    assert_eq!(logger.dropped(), 1);
+    // A record longer than the buffer.
+    log(&logger, Level::Info, "app", &"x".repeat(40));
+    assert_eq!(logger.dropped(), 2);
+    // Only complete records are output.
+    assert_eq!(drain(&logger), "[INFO app] first\r\n");
+    log(&logger, Level::Info, "app", "second record");
+    assert_eq!(drain(&logger), "[INFO app] second record\r\n");
+    assert_eq!(logger.dropped(), 2);
+}
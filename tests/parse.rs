@@ -0,0 +1,74 @@
+//! Number parsers, reading back the output of `Writer`.
+
+mod common;
+
+use common::output;
+use serial_write::parse::{self, ParseError};
+
+#[test]
+fn int_round_trips() {
+    for val in [0, 1, -1, 42, i64::MIN, i64::MAX] {
+        assert_eq!(parse::read_i64(output(|w, s| w.write_i64(val, s))), Ok(val));
+    }
+    for val in [0, 7, u8::MAX] {
+        assert_eq!(parse::read_u8(output(|w, s| w.write_u8(val, s))), Ok(val));
+        assert_eq!(parse::read_u8_hex(output(|w, s| w.write_u8_hex(val, s))), Ok(val));
+    }
+    for val in [0, 0x2000_1FF0, u32::MAX] {
+        assert_eq!(parse::read_u32_hex(output(|w, s| w.write_u32_hex(val, s))), Ok(val));
+    }
+    assert_eq!(parse::read_i16(" -32768\r\n"), Ok(i16::MIN));
+    assert_eq!(parse::read_u16_bin("0b1010"), Ok(10));
+    assert_eq!(parse::read_u16_bin("1010"), Ok(10));
+    assert_eq!(parse::read_u32_hex("0XfF"), Ok(255));
+}
+
+#[test]
+fn float_round_trips() {
+    // 9 and 17 significant digits are enough to read back every f32 and f64.
+    for val in [0.0, -0.0, 1.5e-3, -123.456, f32::MAX, f32::MIN_POSITIVE, f32::EPSILON, 1e-45] {
+        let read = parse::read_f32(output(|w, s| w.write_f32_exp(val, 8, s))).unwrap();
+        assert_eq!(read.to_bits(), val.to_bits(), "{}", val);
+    }
+    for val in [0.1, -2.5e-300, f64::MAX, f64::MIN_POSITIVE, 5e-324] {
+        let read = parse::read_f64(output(|w, s| w.write_f64_exp(val, 16, s))).unwrap();
+        assert_eq!(read.to_bits(), val.to_bits(), "{}", val);
+    }
+    assert_eq!(parse::read_f32(output(|w, s| w.write_f32(-23.456, 2, s))), Ok(-23.46));
+    assert_eq!(parse::read_f32("inf"), Ok(f32::INFINITY));
+    assert!(parse::read_f64(" NaN").unwrap().is_nan());
+}
+
+#[test]
+fn slice_round_trips() {
+    let mut out = [0f32; 4];
+    let text = output(|w, s| w.write_f32_slice(&[1.0, -2.5, 0.25], 2, s));
+    assert_eq!(parse::read_f32_slice(&text, &mut out), Ok(3));
+    assert_eq!(out[..3], [1.0, -2.5, 0.25]);
+    let mut out = [0i32; 4];
+    let text = output(|w, s| w.write_i32_slice(&[-7, 0, i32::MAX], s));
+    assert_eq!(parse::read_i32_slice(&text, &mut out), Ok(3));
+    assert_eq!(out[..3], [-7, 0, i32::MAX]);
+    assert_eq!(parse::read_i32_slice("1 2,3 ,4", &mut out), Ok(4));
+    assert_eq!(out, [1, 2, 3, 4]);
+    assert_eq!(parse::read_u8_slice("[ ]", &mut [0u8; 2]), Ok(0));
+}
+
+#[test]
+fn errors() {
+    assert_eq!(parse::read_u8(""), Err(ParseError::Empty));
+    assert_eq!(parse::read_f32(" \r\n"), Err(ParseError::Empty));
+    assert_eq!(parse::read_i32("12a"), Err(ParseError::InvalidDigit));
+    assert_eq!(parse::read_i32("1 2"), Err(ParseError::InvalidDigit));
+    assert_eq!(parse::read_u8_hex("-0x1F"), Err(ParseError::InvalidDigit));
+    assert_eq!(parse::read_u8_hex("0x+1F"), Err(ParseError::InvalidDigit));
+    assert_eq!(parse::read_u8_bin("0b102"), Err(ParseError::InvalidDigit));
+    assert_eq!(parse::read_u8(b"\xFF"), Err(ParseError::InvalidDigit));
+    assert_eq!(parse::read_f32("1.2.3"), Err(ParseError::InvalidDigit));
+    assert_eq!(parse::read_u8("256"), Err(ParseError::Overflow));
+    assert_eq!(parse::read_i8("-129"), Err(ParseError::Overflow));
+    assert_eq!(parse::read_u16_hex("0x10000"), Err(ParseError::Overflow));
+    assert_eq!(parse::read_f32("1e39"), Err(ParseError::Overflow));
+    assert_eq!(parse::read_f32_slice("1 2 3", &mut [0.0; 2]), Err(ParseError::TooMany));
+    assert_eq!(parse::read_u8_slice("1, x", &mut [0; 2]), Err(ParseError::InvalidDigit));
+}
@@ -0,0 +1,25 @@
+//! Lines for the Arduino IDE Serial Plotter and Teleplot.
+
+mod common;
+
+use common::output;
+use serial_write::plot::{Channel, Plotter};
+
+#[test]
+fn arduino() {
+    let mut plotter = Plotter::new();
+    // -0.5 is rounded half to even, keeping its sign like `{:.0}`.
+    let channels = [Channel::new("target", 1.0, 1), Channel::new("actual", 0.987, 3), Channel::new("err", -0.5, 0)];
+    assert_eq!(output(|_, s| plotter.write_arduino(&channels, s)), "target:1.0,actual:0.987,err:-0\r\n");
+    assert_eq!(output(|_, s| plotter.write_arduino(&channels[..1], s)), "target:1.0\r\n");
+    assert_eq!(output(|_, s| plotter.write_arduino(&[], s)), "\r\n");
+}
+
+#[test]
+fn teleplot() {
+    let mut plotter = Plotter::new();
+    let channels = [Channel::new("target", 1.0, 1), Channel::new("actual", -0.987, 2)];
+    assert_eq!(output(|_, s| plotter.write_teleplot(1200, &channels, s)), ">target:1200:1.0\r\n>actual:1200:-0.99\r\n");
+    assert_eq!(output(|_, s| plotter.write_teleplot(u64::MAX, &channels[1..], s)), ">actual:18446744073709551615:-0.99\r\n");
+    assert_eq!(output(|_, s| plotter.write_teleplot(0, &[], s)), "");
+}
@@ -0,0 +1,48 @@
+//! Progress bars redrawn in place.
+
+mod common;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use common::{output, MockSink};
+use serial_write::progress::{ProgressBar, Spinner};
+
+static NOW: AtomicU64 = AtomicU64::new(0);
+
+/// Clock advancing by a second at each call.
+fn clock() -> u64 {
+    NOW.fetch_add(1_000_000, Ordering::Relaxed)
+}
+
+#[test]
+fn progress_bar_moving_back() {
+    let mut bar = ProgressBar::new(100).with_width(10).with_unit("B").with_clock(clock);
+    let mut sink = MockSink::new();
+    bar.set(10, &mut sink).unwrap();
+    // The rate is measured again from 5, instead of underflowing.
+    bar.set(5, &mut sink).unwrap();
+    sink.data.clear();
+    bar.set(25, &mut sink).unwrap();
+    assert_eq!(sink.as_str(), "\r[##        ]  25%  20.0 B/s  ETA 0:03");
+}
+
+#[test]
+fn progress_bar() {
+    let mut bar = ProgressBar::new(200).with_width(4);
+    assert_eq!(output(|_, s| bar.set(50, s)), "\r[#   ]  25%");
+    assert_eq!(output(|_, s| bar.inc(1000, s)), "\r[####] 100%");
+    assert_eq!(bar.position(), 200);
+    assert_eq!(output(|_, s| bar.finish(s)), "\r[####] 100%\r\n");
+    // Nothing to do is done.
+    let mut bar = ProgressBar::new(0).with_width(2).with_chars("█", "░");
+    assert_eq!(output(|_, s| bar.draw(s)), "\r[██] 100%");
+}
+
+#[test]
+fn spinner() {
+    let mut spinner = Spinner::new();
+    assert_eq!(output(|_, s| spinner.tick("testing", s)), "\r| testing");
+    assert_eq!(output(|_, s| spinner.tick("ok", s)), "\r/ ok     ");
+    // The rest of the longer line is erased.
+    assert_eq!(output(|_, s| spinner.finish("ok", s)), "\rok  \r\n");
+}
@@ -0,0 +1,53 @@
+//! Byte queue drained to another sink.
+
+mod common;
+
+use common::MockSink;
+use serial_write::{RingBuffer, Sink};
+use usb_device::UsbError;
+
+#[test]
+fn wraparound() {
+    let mut ring = RingBuffer::<8>::new();
+    assert_eq!(ring.push(b"abcdef"), 6);
+    let mut sink = MockSink::new().with_capacity(4);
+    assert!(matches!(ring.drain(&mut sink), Ok(4)));
+    assert_eq!(sink.as_str(), "abcd");
+    // "ghijkl" wraps around the end of the buffer.
+    assert_eq!(ring.push(b"ghijkl"), 6);
+    assert_eq!((ring.len(), ring.free()), (8, 0));
+    let mut sink = MockSink::new().with_chunk(3);
+    assert!(matches!(ring.drain(&mut sink), Ok(8)));
+    assert_eq!(sink.as_str(), "efghijkl");
+    assert!(ring.is_empty());
+}
+
+#[test]
+fn full() {
+    let mut ring = RingBuffer::<4>::new();
+    // Only the bytes which fit are written.
+    assert!(matches!(ring.write(b"123456"), Ok(4)));
+    assert!(matches!(ring.write(b"7"), Err(UsbError::WouldBlock)));
+    assert!(matches!(ring.write(b""), Ok(0)));
+    ring.truncate(1);
+    assert_eq!(ring.push(b"xyz!"), 3);
+    let mut sink = MockSink::new();
+    ring.drain(&mut sink).unwrap();
+    assert_eq!(sink.as_str(), "1xyz");
+    ring.push(b"ab");
+    ring.clear();
+    assert!(matches!(ring.drain(&mut sink), Ok(0)));
+}
+
+#[test]
+fn drain_blocked() {
+    let mut ring = RingBuffer::<8>::new();
+    ring.push(b"abc");
+    // The bytes stay in the buffer until the sink accepts them.
+    let mut sink = MockSink::new().with_capacity(0);
+    assert!(matches!(ring.drain(&mut sink), Ok(0)));
+    assert_eq!(ring.len(), 3);
+    let mut sink = MockSink::new().with_capacity(1);
+    assert!(matches!(ring.drain(&mut sink), Ok(1)));
+    assert_eq!(ring.len(), 2);
+}
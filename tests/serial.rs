@@ -0,0 +1,71 @@
+//! `SerialPort` on a mock USB bus.
+
+mod common;
+
+use common::MockBus;
+use serial_write::{Sink, Source, Writer};
+use usb_device::class_prelude::*;
+use usb_device::prelude::*;
+use usbd_serial::SerialPort;
+
+/// Build the device, which completes the allocation of the endpoints of `SerialPort`.
+fn device(alloc: &UsbBusAllocator<MockBus>) -> UsbDevice<'_, MockBus> {
+    UsbDeviceBuilder::new(alloc, UsbVidPid(0x16c0, 0x27dd)).build()
+}
+
+#[test]
+fn write() {
+    let (bus, state) = MockBus::new();
+    let alloc = UsbBusAllocator::new(bus);
+    let mut serial = SerialPort::new(&alloc);
+    let _device = device(&alloc);
+    let mut writer = Writer::new();
+    assert_eq!(writer.writeln_f32(1.5, 2, &mut serial).unwrap(), 6);
+    assert_eq!(state.lock().unwrap().written(), b"1.50\r\n");
+}
+
+#[test]
+fn short_write() {
+    let (bus, state) = MockBus::new();
+    let alloc = UsbBusAllocator::new(bus);
+    let mut serial = SerialPort::new(&alloc);
+    let _device = device(&alloc);
+    // The write buffer of `SerialPort` holds 128 bytes, of which a packet of 64 is sent.
+    assert_eq!(Sink::write(&mut serial, &[b'x'; 200]).unwrap(), 128);
+    assert_eq!(state.lock().unwrap().written().len(), 64);
+}
+
+#[test]
+fn would_block() {
+    let (bus, state) = MockBus::new();
+    state.lock().unwrap().packets_left = Some(0);
+    let alloc = UsbBusAllocator::new(bus);
+    let mut serial = SerialPort::new(&alloc);
+    let _device = device(&alloc);
+    let mut writer = Writer::new();
+    let data = [7u8; 50];
+    // The write buffer of `SerialPort` is full after "[ " and 42 elements of "7, ".
+    match writer.write_u8_slice(&data, &mut serial) {
+        Err((UsbError::WouldBlock, 128)) => {}
+        other => panic!("{:?}", other),
+    }
+    assert!(state.lock().unwrap().written().is_empty());
+    state.lock().unwrap().packets_left = None;
+    while serial.flush().is_err() {}
+    let written = state.lock().unwrap().written();
+    assert_eq!(written.len(), 128);
+    assert!(written.starts_with(b"[ 7, 7, "));
+}
+
+#[test]
+fn read() {
+    let (bus, state) = MockBus::new();
+    let alloc = UsbBusAllocator::new(bus);
+    let mut serial = SerialPort::new(&alloc);
+    let _device = device(&alloc);
+    let mut buf = [0u8; 16];
+    assert!(matches!(Source::read(&mut serial, &mut buf), Err(UsbError::WouldBlock)));
+    state.lock().unwrap().input.extend(b"help\r");
+    assert_eq!(Source::read(&mut serial, &mut buf).unwrap(), 5);
+    assert_eq!(&buf[..5], b"help\r");
+}
@@ -0,0 +1,127 @@
+//! Command shell: tokenizing, dispatch, errors and help.
+
+mod common;
+
+use common::{MockSerial, MockSink};
+use serial_write::parse;
+use serial_write::shell::{tokenize, Command, Shell, ShellError};
+use serial_write::{Sink, Writer};
+
+/// Output each argument in brackets.
+fn echo(args: &[&str], writer: &mut Writer, serial: &mut dyn Sink) -> Result<(), ShellError> {
+    for arg in args {
+        writer.write_str("[", serial)?;
+        writer.write_str(arg, serial)?;
+        writer.write_str("]", serial)?;
+    }
+    writer.write_str("\r\n", serial)?;
+    Ok(())
+}
+
+fn set_gain(args: &[&str], writer: &mut Writer, serial: &mut dyn Sink) -> Result<(), ShellError> {
+    let [gain] = args else { return Err(ShellError::BadArgs) };
+    let gain = parse::read_f32(gain)?;
+    writer.write_str("gain = ", serial)?;
+    writer.write_f32(gain, 3, serial)?;
+    writer.write_str("\r\n", serial)?;
+    Ok(())
+}
+
+static COMMANDS: [Command; 2] = [
+    Command { name: "echo", help: "<words>  Echo the words", handler: echo },
+    Command { name: "set_gain", help: "<gain>  Set the gain", handler: set_gain },
+];
+
+/// Result of `execute` with a shell of up to `A` words, and its output.
+fn execute<const A: usize>(line: &str) -> (Result<(), ShellError>, String) {
+    let mut shell = Shell::<64, A>::new(&COMMANDS);
+    let mut sink = MockSink::new();
+    let res = shell.execute(line, &mut sink);
+    (res, sink.as_str().to_string())
+}
+
+#[test]
+fn words() {
+    let mut words = [""; 8];
+    let n = tokenize("  set 'a b'\t\"c'd\" e\"f\" '' ", &mut words).unwrap();
+    assert_eq!(words[..n], ["set", "a b", "c'd", "e\"f\"", ""]);
+    assert_eq!(tokenize(" \t ", &mut words).unwrap(), 0);
+}
+
+#[test]
+fn arguments() {
+    let (res, out) = execute::<4>("echo x 'y z' \"'w'\"");
+    assert!(res.is_ok());
+    assert_eq!(out, "[x][y z]['w']\r\n");
+    let (res, out) = execute::<4>("set_gain 1.5");
+    assert!(res.is_ok());
+    assert_eq!(out, "gain = 1.500\r\n");
+    let (res, out) = execute::<4>("set_gain x");
+    assert!(matches!(res, Err(ShellError::BadArgs)));
+    assert_eq!(out, "invalid arguments; type \"help\" for the usage\r\n");
+    // An empty line does nothing.
+    let (res, out) = execute::<4>("   ");
+    assert!(res.is_ok());
+    assert_eq!(out, "");
+}
+
+#[test]
+fn unterminated_quote() {
+    let (res, out) = execute::<4>("echo 'abc");
+    assert!(matches!(res, Err(ShellError::UnterminatedQuote)));
+    assert_eq!(out, "unterminated quote\r\n");
+}
+
+#[test]
+fn unknown_command() {
+    let (res, out) = execute::<4>("reboot now");
+    assert!(matches!(res, Err(ShellError::UnknownCommand)));
+    assert_eq!(out, "unknown command; type \"help\" for the list\r\n");
+    let (res, _) = execute::<4>("help reboot");
+    assert!(matches!(res, Err(ShellError::UnknownCommand)));
+}
+
+#[test]
+fn too_many_arguments() {
+    // The command name is one of the 3 words.
+    let (res, out) = execute::<3>("echo a b");
+    assert!(res.is_ok());
+    assert_eq!(out, "[a][b]\r\n");
+    let (res, out) = execute::<3>("echo a b c");
+    assert!(matches!(res, Err(ShellError::TooManyArgs)));
+    assert_eq!(out, "too many arguments\r\n");
+}
+
+#[test]
+fn help() {
+    let (res, out) = execute::<4>("help");
+    assert!(res.is_ok());
+    assert_eq!(out, "  echo      <words>  Echo the words\r\n  set_gain  <gain>  Set the gain\r\n");
+    let (_, out) = execute::<4>("help echo");
+    assert_eq!(out, "  echo  <words>  Echo the words\r\n");
+    let (res, out) = execute::<4>("help echo set_gain");
+    assert!(matches!(res, Err(ShellError::BadArgs)));
+    assert_eq!(out, "invalid arguments; type \"help\" for the usage\r\n");
+}
+
+#[test]
+fn poll() {
+    let mut shell = Shell::<64, 4>::new(&COMMANDS).with_prompt("imu> ");
+    let mut serial = MockSerial::new(b"echo 'a b'\r\n");
+    shell.write_prompt(&mut serial).unwrap();
+    shell.poll(&mut serial).unwrap();
+    // The input is echoed, then the output of the command and the next prompt.
+    assert_eq!(serial.sink.as_str(), "imu> echo 'a b'\r\n[a b]\r\nimu> ");
+    // Nothing more to read.
+    shell.poll(&mut serial).unwrap();
+    assert_eq!(serial.sink.as_str(), "imu> echo 'a b'\r\n[a b]\r\nimu> ");
+}
+
+#[test]
+fn poll_without_echo() {
+    let mut shell = Shell::<8, 4>::new(&COMMANDS).with_prompt("$ ").with_echo(false);
+    let mut serial = MockSerial::new(b"echo 0123456789\recho 1\r");
+    assert!(matches!(shell.poll(&mut serial), Err(ShellError::LineTooLong)));
+    shell.poll(&mut serial).unwrap();
+    assert_eq!(serial.sink.as_str(), "line too long\r\n$ [1]\r\n$ ");
+}
@@ -0,0 +1,102 @@
+//! Sinks writing into memory.
+
+mod common;
+
+use common::{MockSink, MockSource};
+use serial_write::format::*;
+use serial_write::input::LineReader;
+use serial_write::{SliceSink, Sink, Writer};
+use usb_device::UsbError;
+
+#[test]
+fn slice_sink() {
+    let mut buf = [0u8; 8];
+    let mut sink = SliceSink::new(&mut buf);
+    let mut writer = Writer::new();
+    assert!(sink.is_empty());
+    writer.write_i32(-42, &mut sink).unwrap();
+    assert_eq!(sink.as_str(), "-42");
+    match writer.write_str(" µs µs", &mut sink) {
+        Err((UsbError::WouldBlock, 5)) | Ok(5) => {}
+        other => panic!("{:?}", other),
+    }
+    // "µ" is cut off at its second byte.
+    assert_eq!(sink.len(), 8);
+    assert_eq!(sink.as_str(), "-42 µs ");
+    sink.clear();
+    assert!(sink.is_empty());
+}
+
+#[test]
+fn slice_sink_full() {
+    let mut buf = [0u8; 4];
+    let mut sink = SliceSink::new(&mut buf);
+    assert!(matches!(sink.write(b"abc"), Ok(3)));
+    assert!(matches!(sink.write(b"de"), Ok(1)));
+    assert!(matches!(sink.write(b"f"), Err(UsbError::WouldBlock)));
+    assert!(matches!(sink.write(b""), Ok(0)));
+    assert_eq!(sink.as_bytes(), b"abcd");
+    assert_eq!(sink.into_str(), "abcd");
+    assert_eq!(&buf, b"abcd");
+}
+
+#[test]
+fn write_all_retries_short_writes() {
+    let mut sink = MockSink::new().with_chunk(3);
+    let mut writer = Writer::new();
+    writer.write_args(format_args!("{}-{}", "abcdef", 12345), &mut sink).unwrap();
+    assert_eq!(sink.as_str(), "abcdef-12345");
+}
+
+#[test]
+fn line_reader() {
+    let mut source = MockSource::new(b"ab\x7fc\r\nnext").with_chunk(2);
+    let mut reader = LineReader::<16>::new();
+    let mut line = None;
+    while line.is_none() {
+        line = reader.read_line(&mut source).unwrap().map(|l| l.to_string());
+    }
+    assert_eq!(line.as_deref(), Some("ac"));
+}
+
+#[test]
+fn format_into_buffer() {
+    let mut buf = [0u8; 16];
+    assert_eq!(fmt_i32(i32::MIN, &mut buf), "-2147483648");
+    assert_eq!(fmt_f32(3.3, 2, &mut buf), "3.30");
+    assert_eq!(fmt_u16_hex(0xFF, &mut buf), "0x00FF");
+    assert_eq!(fmt_f64_exp(-1.5e-3, 2, &mut buf), "-1.50e-03");
+    assert_eq!(fmt_u8_slice(&[1, 23], &mut buf), "[ 1, 23, ]");
+    assert_eq!(fmt_f32_slice(&[0.5, -1.0], 1, &mut buf), "[ 0.5, -1.0, ]");
+    // Cut off at the end of the buffer.
+    assert_eq!(fmt_u64(u64::MAX, &mut buf[..4]), "1844");
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn heapless_string() {
+    let mut string = heapless::String::<8>::new();
+    let mut writer = Writer::new();
+    writer.write_str("25.0", &mut string).unwrap();
+    // "°" is not cut off.
+    match writer.write_str("°C°C", &mut string) {
+        Ok(3) => {}
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(string.as_str(), "25.0°C");
+    assert!(matches!(Sink::write(&mut string, b"\xFF"), Err(UsbError::ParseError)));
+    assert!(matches!(Sink::write(&mut string, "°".as_bytes()), Err(UsbError::WouldBlock)));
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn heapless_vec() {
+    let mut vec = heapless::Vec::<u8, 4>::new();
+    let mut writer = Writer::new();
+    match writer.write_u32(123456, &mut vec) {
+        Ok(4) => {}
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(&vec[..], b"1234");
+    assert!(matches!(Sink::write(&mut vec, b"5"), Err(UsbError::WouldBlock)));
+}
@@ -0,0 +1,105 @@
+//! Tables with aligned columns and borders.
+
+mod common;
+
+use common::MockSink;
+use serial_write::table::{Align, Border, Column, TableError, TableWriter};
+
+static COLUMNS: [Column; 3] = [
+    Column::new("l", 4),
+    Column::new("r", 4).with_align(Align::Right),
+    Column::new("c", 5).with_align(Align::Center),
+];
+
+#[test]
+fn alignment() {
+    let mut table = TableWriter::new(&COLUMNS);
+    let mut sink = MockSink::new();
+    table.write_header(&mut sink).unwrap();
+    table.cell_str("ab", &mut sink).unwrap();
+    table.cell_i32(-5, &mut sink).unwrap();
+    table.cell_str("xy", &mut sink).unwrap();
+    table.end_row(&mut sink).unwrap();
+    table.write_footer(&mut sink).unwrap();
+    assert_eq!(
+        sink.as_str(),
+        concat!(
+            "+------+------+-------+\r\n",
+            "| l    |    r |   c   |\r\n",
+            "+------+------+-------+\r\n",
+            "| ab   |   -5 |  xy   |\r\n",
+            "+------+------+-------+\r\n",
+        )
+    );
+}
+
+#[test]
+fn unicode_border() {
+    static COLUMNS: [Column; 3] = [
+        Column::new("ch", 2).with_align(Align::Right),
+        Column::new("voltage", 8).with_align(Align::Right).with_nodp(3),
+        Column::new("result", 6).with_align(Align::Center),
+    ];
+    let mut table = TableWriter::new(&COLUMNS).with_border(Border::Unicode);
+    let mut sink = MockSink::new();
+    table.write_header(&mut sink).unwrap();
+    for (ch, v) in [3.3f32, 3.123].iter().enumerate() {
+        table.cell_usize(ch, &mut sink).unwrap();
+        table.cell_f32(*v, &mut sink).unwrap();
+        table.cell_str(if (3.2..3.4).contains(v) { "PASS" } else { "FAIL" }, &mut sink).unwrap();
+        table.end_row(&mut sink).unwrap();
+    }
+    table.write_footer(&mut sink).unwrap();
+    assert_eq!(
+        sink.as_str(),
+        concat!(
+            "┌────┬──────────┬────────┐\r\n",
+            "│ ch │  voltage │ result │\r\n",
+            "├────┼──────────┼────────┤\r\n",
+            "│  0 │    3.300 │  PASS  │\r\n",
+            "│  1 │    3.123 │  FAIL  │\r\n",
+            "└────┴──────────┴────────┘\r\n",
+        )
+    );
+}
+
+#[test]
+fn no_border() {
+    let mut table = TableWriter::new(&COLUMNS[..2]).with_border(Border::None);
+    let mut sink = MockSink::new();
+    table.write_header(&mut sink).unwrap();
+    table.cell_str("ab", &mut sink).unwrap();
+    // The missing cell is output empty.
+    table.end_row(&mut sink).unwrap();
+    table.write_footer(&mut sink).unwrap();
+    assert_eq!(sink.as_str(), " l        r\r\n ----  ----\r\n ab        \r\n");
+}
+
+#[test]
+fn cut_off_and_overflow() {
+    static COLUMNS: [Column; 4] = [
+        Column::new("a", 3),
+        Column::new("b", 3),
+        Column::new("c", 3).with_align(Align::Right),
+        Column::new("d", 4).with_align(Align::Right),
+    ];
+    let mut table = TableWriter::new(&COLUMNS);
+    let mut sink = MockSink::new();
+    table.cell_str("abcdef", &mut sink).unwrap();
+    // Cut off by characters, not bytes.
+    table.cell_str("µµµµ", &mut sink).unwrap();
+    table.cell_i8(-12, &mut sink).unwrap();
+    // Wider than 4 characters with 2 decimal places.
+    table.cell_f32(12.345, &mut sink).unwrap();
+    table.end_row(&mut sink).unwrap();
+    table.cell_u32(1234, &mut sink).unwrap();
+    table.cell_i64(i64::MIN, &mut sink).unwrap();
+    table.cell_empty(&mut sink).unwrap();
+    table.cell_f64(1.5, &mut sink).unwrap();
+    let len = sink.data.len();
+    assert!(matches!(table.cell_u8(1, &mut sink), Err((TableError::TooManyCells, 0))));
+    assert!(matches!(table.cell_f32(1.0, &mut sink), Err((TableError::TooManyCells, 0))));
+    assert_eq!(sink.data.len(), len);
+    table.end_row(&mut sink).unwrap();
+    assert_eq!(sink.as_str(), "| abc | µµµ | -12 | #### |\r\n| ### | ### |     | 1.50 |\r\n");
+}
@@ -0,0 +1,162 @@
+//! Output of each numeric type, from the tables of `examples/test.rs`.
+//!
+//! The floats are rounded to nearest, so some expected values differ from the truncated ones of the example.
+
+mod common;
+
+use common::{output, MockSink};
+use serial_write::Writer;
+use usb_device::UsbError;
+
+#[test]
+fn signed() {
+    assert_eq!(output(|w, s| w.write_i8(i8::MAX, s)), "127");
+    assert_eq!(output(|w, s| w.write_i8(i8::MIN, s)), "-128");
+    assert_eq!(output(|w, s| w.write_i16(i16::MAX, s)), "32767");
+    assert_eq!(output(|w, s| w.write_i16(i16::MIN, s)), "-32768");
+    assert_eq!(output(|w, s| w.write_i32(i32::MAX, s)), "2147483647");
+    assert_eq!(output(|w, s| w.write_i32(i32::MIN, s)), "-2147483648");
+    assert_eq!(output(|w, s| w.write_i64(i64::MAX, s)), "9223372036854775807");
+    assert_eq!(output(|w, s| w.write_i64(i64::MIN, s)), "-9223372036854775808");
+    assert_eq!(output(|w, s| w.write_isize(isize::MAX, s)), isize::MAX.to_string());
+    assert_eq!(output(|w, s| w.write_isize(isize::MIN, s)), isize::MIN.to_string());
+}
+
+#[test]
+fn unsigned() {
+    assert_eq!(output(|w, s| w.write_u8(u8::MAX, s)), "255");
+    assert_eq!(output(|w, s| w.write_u8(u8::MIN, s)), "0");
+    assert_eq!(output(|w, s| w.write_u16(u16::MAX, s)), "65535");
+    assert_eq!(output(|w, s| w.write_u16(u16::MIN, s)), "0");
+    assert_eq!(output(|w, s| w.write_u32(u32::MAX, s)), "4294967295");
+    assert_eq!(output(|w, s| w.write_u32(u32::MIN, s)), "0");
+    assert_eq!(output(|w, s| w.write_u64(u64::MAX, s)), "18446744073709551615");
+    assert_eq!(output(|w, s| w.write_u64(u64::MIN, s)), "0");
+    assert_eq!(output(|w, s| w.write_usize(usize::MAX, s)), usize::MAX.to_string());
+    assert_eq!(output(|w, s| w.write_usize(usize::MIN, s)), "0");
+}
+
+#[test]
+fn hex() {
+    assert_eq!(output(|w, s| w.write_u8_hex(0x0A, s)), "0x0A");
+    assert_eq!(output(|w, s| w.write_u16_hex(0xFF, s)), "0x00FF");
+    assert_eq!(output(|w, s| w.write_u32_hex(0x2000_1FF0, s)), "0x20001FF0");
+    assert_eq!(output(|w, s| w.write_u64_hex(0, s)), "0x0000000000000000");
+}
+
+#[test]
+fn float() {
+    for (nodp, expected) in ["0", "0.0", "0.00", "0.000", "0.0000", "0.00000", "0.000000", "0.0000001"].iter().enumerate() {
+        assert_eq!(output(|w, s| w.write_f32(f32::EPSILON, nodp, s)), *expected);
+    }
+}
+
+#[test]
+fn float_exp() {
+    let expected = [
+        " 1.2e-07", " 1.19e-07", " 1.192e-07", " 1.1921e-07", " 1.19209e-07", " 1.192093e-07", " 1.1920929e-07",
+    ];
+    for (i, expected) in expected.iter().enumerate() {
+        assert_eq!(output(|w, s| w.write_f32_exp(f32::EPSILON, i + 1, s)), *expected);
+    }
+    let expected = [
+        " 2.2e-16", " 2.22e-16", " 2.220e-16", " 2.2204e-16", " 2.22045e-16", " 2.220446e-16", " 2.2204460e-16",
+        " 2.22044605e-16", " 2.220446049e-16", " 2.2204460493e-16", " 2.22044604925e-16", " 2.220446049250e-16",
+        " 2.2204460492503e-16", " 2.22044604925031e-16", " 2.220446049250313e-16",
+    ];
+    for (i, expected) in expected.iter().enumerate() {
+        assert_eq!(output(|w, s| w.write_f64_exp(f64::EPSILON, i + 1, s)), *expected);
+    }
+}
+
+#[test]
+fn float_max() {
+    assert_eq!(output(|w, s| w.write_f32(f32::MAX, 1, s)), "340282346638528859811704183484516925440.0");
+    assert_eq!(output(|w, s| w.write_f32(f32::MIN, 1, s)), "-340282346638528859811704183484516925440.0");
+    assert_eq!(output(|w, s| w.write_f32_exp(f32::MAX, 7, s)), " 3.4028235e038");
+    assert_eq!(output(|w, s| w.write_f32_exp(f32::MIN, 7, s)), "-3.4028235e038");
+    assert_eq!(output(|w, s| w.write_f64_exp(f64::MAX, 16, s)), " 1.7976931348623157e308");
+    assert_eq!(output(|w, s| w.write_f64_exp(f64::MIN, 16, s)), "-1.7976931348623157e308");
+}
+
+#[test]
+fn slices() {
+    assert_eq!(output(|w, s| w.write_usize_slice(&[1, 23, 456, 7890], s)), "[ 1, 23, 456, 7890, ]");
+    assert_eq!(output(|w, s| w.write_f32_slice(&[1.0, 23.45, 678.901], 2, s)), "[ 1.00, 23.45, 678.90, ]");
+    assert_eq!(output(|w, s| w.write_f32_slice_exp(&[1.0, 23.45, 678.901], 2, s)), "[  1.00e000,  2.35e001,  6.79e002, ]");
+}
+
+#[test]
+fn line_prefix() {
+    let mut writer = Writer::new().with_tag("imu").with_level(serial_write::Level::Warn);
+    let mut sink = MockSink::new();
+    writer.writeln_str("a", &mut sink).unwrap();
+    writer.write_u8(1, &mut sink).unwrap();
+    writer.writeln_u8(2, &mut sink).unwrap();
+    assert_eq!(sink.as_str(), "[WARN imu] a\r\n[WARN imu] 12\r\n");
+}
+
+#[test]
+fn line_prefix_embedded_newline() {
+    let mut writer = Writer::new().with_level(serial_write::Level::Info);
+    let mut sink = MockSink::new();
+    writer.write_str("a\r\nb\nc", &mut sink).unwrap();
+    writer.write_str("\n", &mut sink).unwrap();
+    writer.write_str("d", &mut sink).unwrap();
+    assert_eq!(sink.as_str(), "[INFO] a\r\n[INFO] b\n[INFO] c\n[INFO] d");
+}
+
+#[test]
+fn line_prefix_short_write() {
+    let mut writer = Writer::new().with_tag("imu");
+    let mut sink = MockSink::new().with_capacity(7);
+    // "a\n" is cut before the '\n', so the line goes on without a prefix.
+    assert_eq!(writer.write_str("a\nb", &mut sink).unwrap(), 7);
+    sink = MockSink::new();
+    writer.write_str("x\n", &mut sink).unwrap();
+    writer.write_str("y", &mut sink).unwrap();
+    assert_eq!(sink.as_str(), "x\n[imu] y");
+}
+
+#[test]
+fn timestamp() {
+    assert_eq!(output(|w, s| w.write_timestamp(12_345_678, s)), "[   12.345678]");
+    assert_eq!(output(|w, s| w.write_timestamp(999, s)), "[    0.000999]");
+    assert_eq!(output(|w, s| w.write_timestamp(123_456_000_001, s)), "[123456.000001]");
+    let mut writer = Writer::new().with_tag("imu").with_clock(|| 1_500_000);
+    let mut sink = MockSink::new();
+    writer.writeln_str("x", &mut sink).unwrap();
+    assert_eq!(sink.as_str(), "[    1.500000] [imu] x\r\n");
+}
+
+#[test]
+fn short_writes() {
+    let mut writer = Writer::new();
+    let mut sink = MockSink::new().with_chunk(1);
+    assert_eq!(writer.write_str("hello", &mut sink).unwrap(), 1);
+    assert_eq!(sink.as_str(), "h");
+}
+
+#[test]
+fn short_writes_float() {
+    let mut writer = Writer::new();
+    let mut sink = MockSink::new().with_chunk(3);
+    // The digits are staged, and written out in full.
+    assert_eq!(writer.write_f64(-1234.5678, 3, &mut sink).unwrap(), 9);
+    assert_eq!(sink.as_str(), "-1234.568");
+    match writer.write_f32_exp(1.5e-3, 2, &mut MockSink::new().with_capacity(4)) {
+        Err((UsbError::WouldBlock, 4)) => {}
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn would_block() {
+    let mut writer = Writer::new();
+    let mut sink = MockSink::new().with_capacity(4);
+    match writer.write_i32_slice(&[12, 34], &mut sink) {
+        Err((UsbError::WouldBlock, 4)) => {}
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(sink.as_str(), "[ 12");
+}